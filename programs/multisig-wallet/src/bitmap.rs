@@ -0,0 +1,60 @@
+// bitmaps of owner ids used for owner_identities, owner_snapshot and vote_record
+// bits are stored most significant first, id 0 is the highest bit of the first byte
+
+fn mask(id: u16) -> u8 {
+    0b1000_0000 >> (id % 8)
+}
+
+// ids beyond the end of the bitmap are not set, the bitmap may have grown since it was copied
+pub fn is_set(bitmap: &[u8], id: u16) -> bool {
+    bitmap
+        .get((id / 8) as usize)
+        .is_some_and(|byte| byte & mask(id) != 0)
+}
+
+pub fn set(bitmap: &mut [u8], id: u16) {
+    bitmap[(id / 8) as usize] |= mask(id);
+}
+
+pub fn clear(bitmap: &mut [u8], id: u16) {
+    bitmap[(id / 8) as usize] &= !mask(id);
+}
+
+// lowest id that is not set, if any
+pub fn first_unset(bitmap: &[u8]) -> Option<u16> {
+    bitmap
+        .iter()
+        .position(|byte| *byte != u8::MAX)
+        .map(|byte_pos| (byte_pos * 8) as u16 + bitmap[byte_pos].leading_ones() as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundary_bits() {
+        let mut bitmap = [0u8; 2];
+        for id in [0, 7, 8, 15] {
+            set(&mut bitmap, id);
+        }
+        assert_eq!(bitmap, [0b1000_0001, 0b1000_0001]);
+        assert!([0, 7, 8, 15].iter().all(|id| is_set(&bitmap, *id)));
+        assert!([1, 6, 9, 14].iter().all(|id| !is_set(&bitmap, *id)));
+        // ids past the end read as unset
+        assert!(!is_set(&bitmap, 16));
+
+        clear(&mut bitmap, 7);
+        clear(&mut bitmap, 8);
+        assert_eq!(bitmap, [0b1000_0000, 0b0000_0001]);
+    }
+
+    #[test]
+    fn first_unset_id() {
+        assert_eq!(first_unset(&[0b0111_1111]), Some(0));
+        assert_eq!(first_unset(&[0b1111_1110]), Some(7));
+        assert_eq!(first_unset(&[u8::MAX, 0b1011_1111]), Some(9));
+        assert_eq!(first_unset(&[u8::MAX, u8::MAX]), None);
+        assert_eq!(first_unset(&[]), None);
+    }
+}
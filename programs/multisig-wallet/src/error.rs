@@ -26,7 +26,7 @@ pub enum WalletError {
     NotVoted,
     #[msg("Not enough votes to execute the proposal")]
     NotEnoughVotes,
-    #[msg("Wallet already has max (255) number of owners")]
+    #[msg("Wallet already has max (40672) number of owners")]
     MaxOwners,
     #[msg(
         "The number of owner addresses passed and the number of wallet auths passed is different"
@@ -36,4 +36,88 @@ pub enum WalletError {
     IncorrectTokenAccount,
     #[msg("The instruction called and proposal passed do not match")]
     ProposalInstructionMismatch,
+    #[msg("Tokens of the specified mint can not be transferred")]
+    NonTransferableMint,
+    #[msg("Transfer fee of the mint would consume the whole send amount")]
+    TransferFeeExceedsAmount,
+    #[msg("Transfer hook program of the mint is not allowed")]
+    InvalidTransferHook,
+    #[msg("Receive account requires a memo on incoming transfers")]
+    MemoRequired,
+    #[msg("Decimals specified do not match with the decimals of the mint")]
+    DecimalsMismatch,
+    #[msg("Guardian list or guardian threshold specified is invalid")]
+    InvalidGuardians,
+    #[msg("The recovery delay can not be less than 3 days")]
+    TooShortRecoveryDelay,
+    #[msg("The user is not a guardian of the wallet")]
+    NotGuardian,
+    #[msg("The guardian has already approved the recovery")]
+    AlreadyApproved,
+    #[msg("The guardian set has changed since the recovery was initiated")]
+    StaleRecovery,
+    #[msg("The recovery delay has not passed yet")]
+    RecoveryDelayNotPassed,
+    #[msg("Not enough guardian approvals to execute the recovery")]
+    NotEnoughApprovals,
+    #[msg("The wallet is paused")]
+    WalletPaused,
+    #[msg("Pause threshold must be non zero and at most the number of owners")]
+    InvalidPauseThreshold,
+    #[msg("Not enough owners signed to pause the wallet")]
+    NotEnoughSigners,
+    #[msg("Owner weight can not be zero")]
+    ZeroWeight,
+    #[msg("Delegation passed is invalid for the wallet or the user")]
+    InvalidDelegation,
+    #[msg("The delegation has expired")]
+    DelegationExpired,
+    #[msg("The owner was not part of the wallet when the proposal was created")]
+    NotInSnapshot,
+    #[msg("The wallet config has changed since the proposal was created")]
+    StaleProposal,
+    #[msg("The proposal is not open for voting or execution")]
+    ProposalNotOpen,
+    #[msg("Only the proposer can perform this action")]
+    NotProposer,
+    #[msg("The proposal has not been finalised yet")]
+    ProposalNotFinalised,
+    #[msg("The proposal is not a draft")]
+    ProposalNotDraft,
+    #[msg("The account has an old layout and has to be migrated first")]
+    AccountNotMigrated,
+    #[msg("Account passed for migration does not belong to the wallet")]
+    InvalidMigrationAccount,
+    #[msg("Wallet name, description or icon uri specified is invalid")]
+    InvalidMetadata,
+    #[msg("Address book entry specified is invalid")]
+    InvalidAddressBookEntry,
+    #[msg("Address book can not hold more than 100 entries")]
+    AddressBookFull,
+    #[msg("Threshold for unlisted addresses must be above m and at most n")]
+    InvalidAddressBookMode,
+    #[msg("The recipient is not in the address book of the wallet")]
+    RecipientNotInAddressBook,
+    #[msg("Transfer tiers must be sorted by amount with m between 1 and n")]
+    InvalidTransferTiers,
+    #[msg("Transfer tiers can not be set for more than 20 mints")]
+    TooManyTierMints,
+    #[msg("Proposal type or m specified for the threshold override is invalid")]
+    InvalidTypeThreshold,
+    #[msg("Recurring transfer needs a non zero count and an interval of at least an hour")]
+    InvalidSchedule,
+    #[msg("The next instalment of the schedule is not due yet")]
+    PaymentNotDue,
+    #[msg("Vesting must have start before end with the cliff in between")]
+    InvalidVesting,
+    #[msg("Nothing has vested since the last claim")]
+    NothingToClaim,
+    #[msg("The proposal has expired and can only be finalised")]
+    ProposalExpired,
+    #[msg("The proposal has not expired and the wallet config has not changed")]
+    ProposalStillValid,
+    #[msg("Transfer tiers account of the wallet has to be passed")]
+    TransferTiersRequired,
+    #[msg("Chunk of a batch transfer is empty or past its last transfer")]
+    InvalidBatchChunk,
 }
@@ -1,37 +1,59 @@
-use crate::state::{Len, Proposal, ProposalType, VoteCount, WalletAuth, WalletConfig};
+use crate::error::WalletError;
+use crate::state::{
+    AddressBook, Delegation, GuardianSet, Len, LoadCurrent, OtcTrade, PaymentSchedule, Proposal,
+    ProposalType, Recovery, TransferEntry, TransferTiers, Versioned, Vesting, VoteCount,
+    WalletAuth, WalletConfig, WalletMetadata,
+};
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{stake, sysvar};
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 #[derive(Accounts)]
+#[instruction(name: String, m: u8, n: u8, owners: Vec<Pubkey>)]
 pub struct CreateWallet<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
+    pub create_key: Signer<'info>,
     #[account(init, payer = user,
-              space = WalletConfig::len())]
-    pub wallet: Account<'info, WalletConfig>,
+              space = WalletConfig::space(owners.len() + 1),
+              seeds = ["wallet".as_bytes(), create_key.key().as_ref()], bump)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
     #[account(init, payer = user,
               space = WalletAuth::len(),
-              seeds = ["owner".as_bytes().as_ref(), wallet.key().as_ref(), user.key().as_ref()],
+              seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()],
               bump)]
     pub wallet_auth: Account<'info, WalletAuth>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct PauseWallet<'info> {
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub wallet_auth: Account<'info, WalletAuth>,
+}
+
 #[derive(Accounts)]
 pub struct CreateTokenAccount<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    pub wallet: Account<'info, WalletConfig>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
     /// CHECK: pda acting as the authority of all wallet token accounts
-    #[account(seeds = ["authority".as_bytes().as_ref(), wallet.key().as_ref()], bump)]
+    #[account(seeds = ["authority".as_bytes(), wallet.key().as_ref()], bump)]
     pub wallet_authority: UncheckedAccount<'info>,
-    pub mint: Account<'info, Mint>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
     #[account(init, payer = payer,
               associated_token::mint = mint,
-              associated_token::authority = wallet_authority)]
-    pub account: Account<'info, TokenAccount>,
-    pub token_program: Program<'info, Token>,
+              associated_token::authority = wallet_authority,
+              associated_token::token_program = token_program)]
+    pub account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -41,83 +63,241 @@ pub struct GiveUpOwnership<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(mut)]
-    pub wallet: Account<'info, WalletConfig>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
     #[account(mut, close = user,
-              seeds = ["owner".as_bytes().as_ref(), wallet.key().as_ref(), user.key().as_ref()], bump)]
+              seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
     pub wallet_auth: Account<'info, WalletAuth>,
     /// CHECK: pda acting as the authority of all wallet token accounts
-    #[account(seeds = ["authority".as_bytes().as_ref(), wallet.key().as_ref()], bump)]
+    #[account(seeds = ["authority".as_bytes(), wallet.key().as_ref()], bump)]
     pub wallet_authority: Option<UncheckedAccount<'info>>,
-    pub token_program: Option<Program<'info, Token>>,
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct RotateOwnerKey<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut)]
+    pub new_user: Signer<'info>,
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, close = user,
+              seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub wallet_auth: Account<'info, WalletAuth>,
+    #[account(init, payer = new_user, space = WalletAuth::len(),
+              seeds = ["owner".as_bytes(), wallet.key().as_ref(), new_user.key().as_ref()], bump)]
+    pub new_wallet_auth: Account<'info, WalletAuth>,
+    /// CHECK: delegation of the old key, closed if it exists
+    #[account(mut,
+              seeds = ["delegation".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump)]
+    pub delegation: UncheckedAccount<'info>,
+    /// CHECK: recovery of the old key, closed if it exists
+    #[account(mut,
+              seeds = ["recovery".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump)]
+    pub recovery: UncheckedAccount<'info>,
+    /// CHECK: initiator of the recovery, only needed if it exists and checked against it
+    #[account(mut)]
+    pub recovery_initiator: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+#[instruction(proposal_type: ProposalType)]
 pub struct CreateProposal<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    pub wallet: Account<'info, WalletConfig>,
-    #[account(seeds = ["owner".as_bytes().as_ref(), wallet.key().as_ref(), user.key().as_ref()], bump)]
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
     pub wallet_auth: Account<'info, WalletAuth>,
-    #[account(init, payer = user, space = Proposal::len())]
+    #[account(init, payer = user, space = Proposal::space(&proposal_type),
+              seeds = ["proposal".as_bytes(), wallet.key().as_ref(), wallet.load_current()?.proposal_count.to_le_bytes().as_ref()], bump)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(init, payer = user, space = VoteCount::space(WalletConfig::bitmap_len(&wallet)),
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    pub system_program: Program<'info, System>,
+
+    // required in case of creating a transfer or recurring transfer proposal
+    pub receive_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    // checked for transfer recipients if the wallet has an address book mode set
+    #[account(seeds = ["address_book".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = address_book.is_current() @ WalletError::AccountNotMigrated)]
+    pub address_book: Option<Account<'info, AddressBook>>,
+    // required for transfers once the wallet has transfer tiers set
+    #[account(seeds = ["transfer_tiers".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = transfer_tiers.is_current() @ WalletError::AccountNotMigrated)]
+    pub transfer_tiers: Option<Account<'info, TransferTiers>>,
+}
+
+#[derive(Accounts)]
+#[instruction(transfers: Vec<TransferEntry>)]
+pub struct AppendTransfers<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, constraint = proposal.proposer == user.key() @ WalletError::NotProposer,
+              realloc = proposal.to_account_info().data_len() + transfers.len() * TransferEntry::LEN,
+              realloc::payer = user, realloc::zero = false,
+              constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
     pub proposal: Account<'info, Proposal>,
-    #[account(init, payer = user, space = VoteCount::len(),
-              seeds = ["votes".as_bytes().as_ref(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
-    pub vote_count: Account<'info, VoteCount>,
     pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ActivateProposal<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.proposer == user.key() @ WalletError::NotProposer,
+              constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    // the owner bitmap of the wallet may have grown since the draft was created
+    #[account(mut,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump,
+              realloc = VoteCount::space(WalletConfig::bitmap_len(&wallet)),
+              realloc::payer = user, realloc::zero = false)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = ["address_book".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = address_book.is_current() @ WalletError::AccountNotMigrated)]
+    pub address_book: Option<Account<'info, AddressBook>>,
+    // required for transfers once the wallet has transfer tiers set
+    #[account(seeds = ["transfer_tiers".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = transfer_tiers.is_current() @ WalletError::AccountNotMigrated)]
+    pub transfer_tiers: Option<Account<'info, TransferTiers>>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct DelegateVote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub wallet_auth: Account<'info, WalletAuth>,
+    // the delegate has to be an owner of the wallet as well
+    #[account(seeds = ["owner".as_bytes(), wallet.key().as_ref(), delegate.as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = delegate_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub delegate_auth: Account<'info, WalletAuth>,
+    #[account(init_if_needed, payer = user, space = Delegation::len(),
+              seeds = ["delegation".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump)]
+    pub delegation: Account<'info, Delegation>,
+    pub system_program: Program<'info, System>,
+}
 
-    // required in case of creating a transfer proposal
-    pub receive_account: Option<Account<'info, TokenAccount>>,
+#[derive(Accounts)]
+pub struct RevokeDelegation<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, close = user,
+              seeds = ["delegation".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              constraint = delegation.is_current() @ WalletError::AccountNotMigrated)]
+    pub delegation: Account<'info, Delegation>,
 }
 
 // used for vote and revoke_vote instruction
 #[derive(Accounts)]
 pub struct Voting<'info> {
     pub user: Signer<'info>,
-    pub wallet: Account<'info, WalletConfig>,
-    #[account(seeds = ["owner".as_bytes().as_ref(), wallet.key().as_ref(), user.key().as_ref()], bump)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
     pub wallet_auth: Account<'info, WalletAuth>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
     pub proposal: Account<'info, Proposal>,
     #[account(mut,
-              seeds = ["votes".as_bytes().as_ref(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
-    pub vote_count: Account<'info, VoteCount>,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+}
+
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.proposer == user.key() @ WalletError::NotProposer,
+              constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = user,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+}
+
+#[derive(Accounts)]
+pub struct CloseProposal<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, close = user,
+              constraint = proposal.proposer == user.key() @ WalletError::NotProposer,
+              constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct Finalize<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
 pub struct TransferFunds<'info> {
-    pub wallet: Account<'info, WalletConfig>,
-    #[account(mut, close = proposer)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
     pub proposal: Account<'info, Proposal>,
     #[account(mut, close = proposer,
-              seeds = ["votes".as_bytes().as_ref(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
-    pub vote_count: Account<'info, VoteCount>,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
     /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
     #[account(mut, address = proposal.proposer)]
     pub proposer: UncheckedAccount<'info>,
-    #[account(mut, token::authority = wallet_authority)]
-    pub send_account: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub receive_account: Account<'info, TokenAccount>,
+    #[account(mut, token::authority = wallet_authority, token::token_program = token_program)]
+    pub send_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::token_program = token_program)]
+    pub receive_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
     /// CHECK: pda acting as the authority of all wallet token accounts
-    #[account(seeds=["authority".as_bytes().as_ref(), wallet.key().as_ref()], bump)]
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
     pub wallet_authority: UncheckedAccount<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
 pub struct AddOwner<'info> {
-    pub wallet: Account<'info, WalletConfig>,
-    #[account(mut, close = proposer)]
+    #[account(mut, realloc = WalletConfig::space_for_new_owner(&wallet)?,
+              realloc::payer = payer, realloc::zero = false)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
     pub proposal: Account<'info, Proposal>,
     #[account(mut, close = proposer,
-              seeds = ["votes".as_bytes().as_ref(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
-    pub vote_count: Account<'info, VoteCount>,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
     /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
     #[account(mut, address = proposal.proposer)]
     pub proposer: UncheckedAccount<'info>,
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(init, payer = payer, space = WalletAuth::len(),
-              seeds = ["owner".as_bytes().as_ref(), wallet.key().as_ref(),
+              seeds = ["owner".as_bytes(), wallet.key().as_ref(),
                        (if let ProposalType::AddOwner{user}=proposal.proposal { user }
                         else { panic!("redundant account, not an add owner proposal") }).as_ref()], bump)]
     pub wallet_auth: Account<'info, WalletAuth>,
@@ -126,13 +306,566 @@ pub struct AddOwner<'info> {
 
 #[derive(Accounts)]
 pub struct ChangeLifetime<'info> {
-    pub wallet: Account<'info, WalletConfig>,
-    #[account(mut, close = proposer)]
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardians<'info> {
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(init_if_needed, payer = payer, space = GuardianSet::len(),
+              seeds = ["guardians".as_bytes(), wallet.key().as_ref()], bump)]
+    pub guardian_set: Account<'info, GuardianSet>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["guardians".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = guardian_set.is_current() @ WalletError::AccountNotMigrated)]
+    pub guardian_set: Account<'info, GuardianSet>,
+    // wallet auth of the owner whose key is to be replaced
+    #[account(has_one = wallet,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub wallet_auth: Account<'info, WalletAuth>,
+    #[account(init, payer = user, space = Recovery::len(),
+              seeds = ["recovery".as_bytes(), wallet.key().as_ref(), wallet_auth.owner.as_ref()], bump)]
+    pub recovery: Account<'info, Recovery>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["guardians".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = guardian_set.is_current() @ WalletError::AccountNotMigrated)]
+    pub guardian_set: Account<'info, GuardianSet>,
+    #[account(mut, has_one = wallet,
+              constraint = recovery.is_current() @ WalletError::AccountNotMigrated)]
+    pub recovery: Account<'info, Recovery>,
+}
+
+#[derive(Accounts)]
+pub struct VetoRecovery<'info> {
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["owner".as_bytes(), wallet.key().as_ref(), user.key().as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub wallet_auth: Account<'info, WalletAuth>,
+    #[account(mut, has_one = wallet, close = initiator,
+              constraint = recovery.is_current() @ WalletError::AccountNotMigrated)]
+    pub recovery: Account<'info, Recovery>,
+    /// CHECK: initiator will receive funds from closing the recovery, just need to check the address
+    #[account(mut, address = recovery.initiator)]
+    pub initiator: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    pub user: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["guardians".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = guardian_set.is_current() @ WalletError::AccountNotMigrated)]
+    pub guardian_set: Account<'info, GuardianSet>,
+    #[account(mut, has_one = wallet, close = initiator,
+              constraint = recovery.is_current() @ WalletError::AccountNotMigrated)]
+    pub recovery: Account<'info, Recovery>,
+    /// CHECK: initiator will receive funds from closing the recovery, just need to check the address
+    #[account(mut, address = recovery.initiator)]
+    pub initiator: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(seeds = ["guardians".as_bytes(), wallet.key().as_ref()], bump,
+              constraint = guardian_set.is_current() @ WalletError::AccountNotMigrated)]
+    pub guardian_set: Account<'info, GuardianSet>,
+    #[account(mut, has_one = wallet, close = initiator,
+              constraint = recovery.is_current() @ WalletError::AccountNotMigrated)]
+    pub recovery: Account<'info, Recovery>,
+    /// CHECK: initiator will receive funds from closing the recovery, just need to check the address
+    #[account(mut, address = recovery.initiator)]
+    pub initiator: UncheckedAccount<'info>,
+    #[account(mut, close = payer,
+              seeds = ["owner".as_bytes(), wallet.key().as_ref(), recovery.owner.as_ref()], bump,
+              has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub wallet_auth: Account<'info, WalletAuth>,
+    #[account(init, payer = payer, space = WalletAuth::len(),
+              seeds = ["owner".as_bytes(), wallet.key().as_ref(), recovery.new_owner.as_ref()], bump)]
+    pub new_wallet_auth: Account<'info, WalletAuth>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeWalletState<'info> {
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeOwnerWeight<'info> {
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    // owner accounts are only created at their pda, so matching the wallet and the owner
+    // named by the proposal is enough
+    #[account(mut, has_one = wallet @ WalletError::InvalidWalletAuth,
+              constraint = matches!(proposal.proposal, ProposalType::ChangeOwnerWeight { owner, .. }
+                                    if owner == wallet_auth.owner) @ WalletError::ProposalInstructionMismatch,
+              constraint = wallet_auth.is_current() @ WalletError::AccountNotMigrated)]
+    pub wallet_auth: Account<'info, WalletAuth>,
+}
+
+#[derive(Accounts)]
+pub struct BatchTransferFunds<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    // closed with the last chunk of the batch
+    #[account(mut,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    /// CHECK: pda acting as the authority of all wallet token accounts
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// anyone can pay for the migration, only the layout of the accounts changes
+#[derive(Accounts)]
+pub struct MigrateWallet<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// anyone can close a proposal of the old layout, the rent goes back to its proposer
+#[derive(Accounts)]
+pub struct CloseLegacyProposal<'info> {
+    /// CHECK: checked against the proposer stored in the proposal
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+    /// CHECK: only used for the vote count address, the wallet may not be migrated yet
+    pub wallet: UncheckedAccount<'info>,
+    /// CHECK: layout and wallet are checked in the handler
+    #[account(mut, owner = crate::ID)]
+    pub proposal: UncheckedAccount<'info>,
+    /// CHECK: layout is checked in the handler
+    #[account(mut, owner = crate::ID,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // resized in the instruction to fit the new metadata
+    #[account(init_if_needed, payer = payer, space = WalletMetadata::space("", ""),
+              seeds = ["metadata".as_bytes(), wallet.key().as_ref()], bump)]
+    pub metadata: Account<'info, WalletMetadata>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAddressBook<'info> {
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // resized in the instruction to fit the new entries
+    #[account(init_if_needed, payer = payer, space = AddressBook::space(&[]),
+              seeds = ["address_book".as_bytes(), wallet.key().as_ref()], bump)]
+    pub address_book: Account<'info, AddressBook>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferTiers<'info> {
+    #[account(mut)]
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    // resized in the instruction to fit the new tiers
+    #[account(init_if_needed, payer = payer, space = TransferTiers::space(&[]),
+              seeds = ["transfer_tiers".as_bytes(), wallet.key().as_ref()], bump)]
+    pub transfer_tiers: Account<'info, TransferTiers>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StartRecurringTransfer<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(init, payer = payer, space = PaymentSchedule::len(),
+              seeds = ["schedule".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub schedule: Account<'info, PaymentSchedule>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankPayment<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, has_one = wallet,
+              constraint = schedule.is_current() @ WalletError::AccountNotMigrated)]
+    pub schedule: Account<'info, PaymentSchedule>,
+    /// CHECK: payer of the schedule receives the rent after the last instalment, just need to check the address
+    #[account(mut, address = schedule.payer)]
+    pub schedule_payer: UncheckedAccount<'info>,
+    #[account(mut, token::authority = wallet_authority, token::mint = mint,
+              token::token_program = token_program)]
+    pub send_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, address = schedule.receive_account)]
+    pub receive_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(address = schedule.mint, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: pda acting as the authority of all wallet token accounts
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecurringTransfer<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut, has_one = wallet, close = schedule_payer,
+              constraint = schedule.is_current() @ WalletError::AccountNotMigrated)]
+    pub schedule: Account<'info, PaymentSchedule>,
+    /// CHECK: payer of the schedule receives the rent, just need to check the address
+    #[account(mut, address = schedule.payer)]
+    pub schedule_payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVesting<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
     pub proposal: Account<'info, Proposal>,
     #[account(mut, close = proposer,
-              seeds = ["votes".as_bytes().as_ref(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
-    pub vote_count: Account<'info, VoteCount>,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
     /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
     #[account(mut, address = proposal.proposer)]
     pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(init, payer = payer, space = Vesting::len(),
+              seeds = ["vesting".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vesting: Account<'info, Vesting>,
+    #[account(init, payer = payer,
+              seeds = ["escrow".as_bytes(), vesting.key().as_ref()], bump,
+              token::mint = mint, token::authority = vesting, token::token_program = token_program)]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::authority = wallet_authority, token::mint = mint,
+              token::token_program = token_program)]
+    pub send_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: pda acting as the authority of all wallet token accounts
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    pub beneficiary: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, has_one = wallet, has_one = beneficiary, has_one = escrow, has_one = mint,
+              has_one = payer,
+              constraint = vesting.is_current() @ WalletError::AccountNotMigrated)]
+    pub vesting: Account<'info, Vesting>,
+    /// CHECK: payer of the vesting receives the rent, just need to check the address
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub receive_account: InterfaceAccount<'info, TokenAccount>,
+    // mutable for harvesting withheld transfer fees when the escrow is closed
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClawbackVesting<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut, has_one = wallet, has_one = escrow, has_one = mint, has_one = payer,
+              constraint = vesting.is_current() @ WalletError::AccountNotMigrated)]
+    pub vesting: Account<'info, Vesting>,
+    /// CHECK: payer of the vesting receives the rent, just need to check the address
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+    // unvested tokens are returned to this account of the wallet
+    #[account(mut, token::authority = wallet_authority, token::mint = mint,
+              token::token_program = token_program)]
+    pub receive_account: InterfaceAccount<'info, TokenAccount>,
+    // mutable for harvesting withheld transfer fees when the escrow is closed
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: pda acting as the authority of all wallet token accounts
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct EscrowOtcTrade<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(init, payer = payer, space = OtcTrade::len(),
+              seeds = ["otc_trade".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub trade: Account<'info, OtcTrade>,
+    #[account(init, payer = payer,
+              seeds = ["escrow".as_bytes(), trade.key().as_ref()], bump,
+              token::mint = give_mint, token::authority = trade,
+              token::token_program = token_program)]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::authority = wallet_authority, token::mint = give_mint,
+              token::token_program = token_program)]
+    pub send_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub give_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: pda acting as the authority of all wallet token accounts
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CompleteOtcTrade<'info> {
+    pub counterparty: Signer<'info>,
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, has_one = wallet, has_one = counterparty, has_one = escrow,
+              has_one = give_mint, has_one = want_mint, close = payer,
+              constraint = trade.is_current() @ WalletError::AccountNotMigrated)]
+    pub trade: Account<'info, OtcTrade>,
+    /// CHECK: payer of the trade receives the rent, just need to check the address
+    #[account(mut, address = trade.payer)]
+    pub payer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+    // counterparty account receiving the give side
+    #[account(mut, token::mint = give_mint, token::token_program = give_token_program)]
+    pub counterparty_receive_account: InterfaceAccount<'info, TokenAccount>,
+    // counterparty account paying the want side
+    #[account(mut, token::authority = counterparty, token::mint = want_mint,
+              token::token_program = want_token_program)]
+    pub counterparty_send_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::authority = wallet_authority, token::mint = want_mint,
+              token::token_program = want_token_program)]
+    pub receive_account: InterfaceAccount<'info, TokenAccount>,
+    // mutable for harvesting withheld transfer fees when the escrow is closed
+    #[account(mut, mint::token_program = give_token_program)]
+    pub give_mint: InterfaceAccount<'info, Mint>,
+    #[account(mint::token_program = want_token_program)]
+    pub want_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: pda acting as the authority of all wallet token accounts
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    pub give_token_program: Interface<'info, TokenInterface>,
+    pub want_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOtcTrade<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut, has_one = wallet, has_one = escrow, has_one = give_mint, close = payer,
+              constraint = trade.is_current() @ WalletError::AccountNotMigrated)]
+    pub trade: Account<'info, OtcTrade>,
+    /// CHECK: payer of the trade receives the rent, just need to check the address
+    #[account(mut, address = trade.payer)]
+    pub payer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub escrow: InterfaceAccount<'info, TokenAccount>,
+    // escrowed tokens are returned to this account of the wallet
+    #[account(mut, token::authority = wallet_authority, token::mint = give_mint,
+              token::token_program = token_program)]
+    pub receive_account: InterfaceAccount<'info, TokenAccount>,
+    // mutable for harvesting withheld transfer fees when the escrow is closed
+    #[account(mut, mint::token_program = token_program)]
+    pub give_mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: pda acting as the authority of all wallet token accounts
+    #[account(seeds=["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStake<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    /// CHECK: pda acting as the authority of all wallet token and stake accounts, funds the stake
+    #[account(mut, seeds = ["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    /// CHECK: stake account created and initialized in the instruction
+    #[account(mut, seeds = ["stake".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub stake: UncheckedAccount<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: native stake program
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageStake<'info> {
+    pub wallet: AccountLoader<'info, WalletConfig>,
+    #[account(mut, constraint = proposal.is_current() @ WalletError::AccountNotMigrated)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut, close = proposer,
+              seeds = ["votes".as_bytes(), wallet.key().as_ref(), proposal.key().as_ref()], bump)]
+    pub vote_count: AccountLoader<'info, VoteCount>,
+    /// CHECK: proposer will receive funds from closing the accounts, just need to check the address
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    /// CHECK: pda acting as the authority of all wallet token and stake accounts
+    #[account(mut, seeds = ["authority".as_bytes(), wallet.key().as_ref()], bump)]
+    pub wallet_authority: UncheckedAccount<'info>,
+    /// CHECK: stake account of the proposal, the stake program checks the authority
+    #[account(mut, owner = stake::program::ID)]
+    pub stake: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: stake history sysvar
+    #[account(address = sysvar::stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    /// CHECK: native stake program
+    #[account(address = stake::program::ID)]
+    pub stake_program: UncheckedAccount<'info>,
+
+    // required in case of delegating the stake
+    /// CHECK: vote account checked against the proposal
+    pub vote_account: Option<UncheckedAccount<'info>>,
+    /// CHECK: stake config account
+    #[account(address = stake::config::ID)]
+    pub stake_config: Option<UncheckedAccount<'info>>,
 }
@@ -1,15 +1,41 @@
+pub mod bitmap;
 pub mod error;
 pub mod instruction_accounts;
 pub mod state;
 
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
-    account_info::next_account_info, program::invoke_signed, rent::Rent, system_instruction,
+    account_info::next_account_info,
+    program::{invoke, invoke_signed},
+    rent::Rent,
+    stake::{
+        self,
+        state::{Authorized, Lockup, StakeStateV2},
+    },
+    system_instruction,
+};
+use anchor_lang::Discriminator;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        memo_transfer::MemoTransfer,
+        non_transferable::NonTransferable,
+        transfer_fee::{TransferFeeAmount, TransferFeeConfig},
+        transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+    },
+};
+use anchor_spl::token_interface::{
+    self, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
 };
-use anchor_spl::token::{self, TokenAccount, Transfer};
 use error::*;
 use instruction_accounts::*;
-use state::{Len, Proposal, ProposalType, RawWalletAuth, VoteCount, WalletAuth, WalletConfig};
+use state::{
+    AddressBook, AddressBookMode, Delegation, GuardianSet, LegacyProposal, LegacyWalletAuth,
+    LegacyWalletConfig, Len, LoadCurrent, MintTiers, OtcTrade, PaymentSchedule, Proposal,
+    ProposalStatus, ProposalTimestamps, ProposalType, RawWalletAuth, Recovery, TransferEntry,
+    TransferTiers, Versioned, Vesting, VoteCount, WalletAuth, WalletConfig, WalletMetadata,
+    ACCOUNT_VERSION,
+};
 use std::convert::TryInto;
 
 declare_id!("39FJGfw5aXNhpNN3bJAVQeDpm6AsNRupUD8L7NBPvABp");
@@ -28,7 +54,7 @@ pub mod multisig_wallet {
         proposal_lifetime: i64,
     ) -> Result<()> {
         require!(
-            name.len() > 0 && name.len() < WalletConfig::MAX_NAME_LEN,
+            !name.is_empty() && name.len() < WalletConfig::MAX_NAME_LEN,
             WalletError::InvalidName
         );
         require!(m > 0 && n > 0, WalletError::ZeroParameters);
@@ -39,12 +65,19 @@ pub mod multisig_wallet {
             WalletError::SizeMismatch
         );
         require!(proposal_lifetime >= 600, WalletError::TooShortDuration);
+        require!(
+            owners.len() < WalletConfig::MAX_OWNERS as usize,
+            WalletError::MaxOwners
+        );
 
         ctx.accounts.wallet_auth.set_inner(WalletAuth {
             owner: ctx.accounts.user.key(),
             wallet: ctx.accounts.wallet.key(),
             id: 0,
             added_time: Clock::get()?.unix_timestamp,
+            weight: 1,
+            version: ACCOUNT_VERSION,
+            reserved: [0; 32],
         });
 
         let wallet_key = ctx.accounts.wallet.key();
@@ -62,11 +95,7 @@ pub mod multisig_wallet {
             wallet_auth_account = next_account_info(account_info_iter)?;
             // verify the wallet_auth passed has correct address
             (wallet_auth_address, bump) = Pubkey::find_program_address(
-                &[
-                    "owner".as_bytes().as_ref(),
-                    wallet_key.as_ref(),
-                    owner.as_ref(),
-                ],
+                &["owner".as_bytes(), wallet_key.as_ref(), owner.as_ref()],
                 &ID,
             );
             require_keys_eq!(
@@ -85,7 +114,7 @@ pub mod multisig_wallet {
                 ),
                 &[user.clone(), wallet_auth_account.clone()],
                 &[&[
-                    "owner".as_bytes().as_ref(),
+                    "owner".as_bytes(),
                     wallet_key.as_ref(),
                     owner.as_ref(),
                     &[bump],
@@ -93,39 +122,35 @@ pub mod multisig_wallet {
             )?;
             // initialise the wallet_auth
             wallet_auth = RawWalletAuth {
-                discriminator: [131, 111, 99, 219, 98, 234, 255, 179],
+                discriminator: WalletAuth::DISCRIMINATOR,
                 owner,
                 wallet: wallet_key,
                 id,
                 added_time: current_time,
+                weight: 1,
+                version: ACCOUNT_VERSION,
+                reserved: [0; 32],
             };
             wallet_auth.serialize(&mut &mut wallet_auth_account.data.borrow_mut()[..])?;
             id += 1;
         }
         // initialise wallet_config
-        let mut owner_identities = [0u8; 32];
-        let last_byte = ((id - 1) / 8) as usize;
-        let last_owner_pos = (id - 1) % 8;
-        for byte in 0..last_byte {
-            owner_identities[byte] = 255;
-        }
-        let mut record_string = String::new();
-        for _ in 0..=last_owner_pos {
-            record_string.push('1');
-        }
-        for _ in (last_owner_pos + 1)..8 {
-            record_string.push('0');
-        }
-        owner_identities[last_byte] = u8::from_str_radix(&record_string, 2).unwrap();
-        let other_owner_count: u8 = ctx.remaining_accounts.len().try_into().unwrap();
-        ctx.accounts.wallet.set_inner(WalletConfig {
-            name,
-            m,
-            n,
-            owners: other_owner_count + 1,
-            owner_identities,
-            proposal_lifetime,
-        });
+        let other_owner_count: u16 = ctx.remaining_accounts.len().try_into().unwrap();
+        ctx.accounts.wallet.load_init()?.version = ACCOUNT_VERSION;
+        let (mut wallet, mut owner_identities) =
+            WalletConfig::load_mut_with_owners(&ctx.accounts.wallet)?;
+        for owner_id in 0..id {
+            bitmap::set(&mut owner_identities, owner_id);
+        }
+        wallet.name[..name.len()].copy_from_slice(name.as_bytes());
+        wallet.m = m;
+        wallet.n = n;
+        wallet.owners = other_owner_count + 1;
+        wallet.total_weight = other_owner_count as u64 + 1;
+        wallet.proposal_lifetime = proposal_lifetime;
+        wallet.pause_threshold = 1;
+        wallet.create_key = ctx.accounts.create_key.key();
+        wallet.bump = ctx.bumps.wallet;
         Ok(())
     }
     pub fn give_up_ownership<'info>(
@@ -134,258 +159,2219 @@ pub mod multisig_wallet {
         // if owner count = 1 and accounts present in remaining accounts
         // transfer the funds to specified accounts
         // else update owner count and owner record in wallet
-        let wallet = &mut ctx.accounts.wallet;
+        let (mut wallet, mut owner_identities) =
+            WalletConfig::load_mut_with_owners(&ctx.accounts.wallet)?;
         if wallet.owners == 1 {
-            if ctx.remaining_accounts.len() == 0 {
+            if ctx.remaining_accounts.is_empty() {
                 return Ok(());
             }
+            require!(!wallet.is_paused(), WalletError::WalletPaused);
             require_eq!(
-                ctx.remaining_accounts.len() % 2,
+                ctx.remaining_accounts.len() % 3,
                 0,
                 WalletError::InsufficientAccounts
             );
+            let wallet_key = ctx.accounts.wallet.key();
             let authority = ctx
                 .accounts
                 .wallet_authority
                 .as_ref()
                 .unwrap()
                 .to_account_info();
-            let authority_bump = *ctx.bumps.get("wallet_authority").unwrap();
+            let authority_bump = ctx.bumps.wallet_authority;
             let cpi_program = ctx
                 .accounts
                 .token_program
                 .as_ref()
                 .unwrap()
                 .to_account_info();
+            // accounts are passed as (send account, mint, receive account) triples
             let account_info_iter = &mut ctx.remaining_accounts.iter();
             let mut send_account;
+            let mut mint;
             let mut receive_account;
             let mut amount;
+            let mut decimals;
             let mut cpi_context;
             while account_info_iter.len() > 0 {
                 send_account = next_account_info(account_info_iter)?.clone();
+                mint = next_account_info(account_info_iter)?.clone();
                 receive_account = next_account_info(account_info_iter)?.clone();
                 amount =
                     TokenAccount::try_deserialize(&mut &send_account.data.borrow()[..])?.amount;
+                decimals = Mint::try_deserialize(&mut &mint.data.borrow()[..])?.decimals;
                 cpi_context = CpiContext::new(
                     cpi_program.clone(),
-                    Transfer {
+                    TransferChecked {
                         from: send_account,
+                        mint,
                         to: receive_account,
                         authority: authority.clone(),
                     },
                 );
-                token::transfer(
-                    cpi_context
-                        .with_signer(&[&["authority".as_bytes().as_ref(), &[authority_bump]]]),
+                token_interface::transfer_checked(
+                    cpi_context.with_signer(&[&[
+                        "authority".as_bytes(),
+                        wallet_key.as_ref(),
+                        &[authority_bump],
+                    ]]),
                     amount,
+                    decimals,
                 )?;
             }
+        } else {
+            // remove the owner's id and weight from the wallet
+            bitmap::clear(&mut owner_identities, ctx.accounts.wallet_auth.id);
+            wallet.owners = wallet.owners.checked_sub(1).unwrap();
+            wallet.total_weight = wallet
+                .total_weight
+                .checked_sub(ctx.accounts.wallet_auth.weight as u64)
+                .unwrap();
+            // votes of the owner on open proposals no longer count
+            wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+        }
+        Ok(())
+    }
+    pub fn rotate_owner_key(ctx: Context<RotateOwnerKey>) -> Result<()> {
+        // the new key keeps the id of the owner, open proposals were voted on with the old
+        // key and go stale
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+        let wallet_auth = &ctx.accounts.wallet_auth;
+        ctx.accounts.new_wallet_auth.set_inner(WalletAuth {
+            owner: ctx.accounts.new_user.key(),
+            wallet: wallet_auth.wallet,
+            id: wallet_auth.id,
+            added_time: wallet_auth.added_time,
+            weight: wallet_auth.weight,
+            version: ACCOUNT_VERSION,
+            reserved: [0; 32],
+        });
+        // a delegation or recovery of the old key would be left behind, the new key
+        // can delegate again and the owner is evidently not locked out
+        let delegation = ctx.accounts.delegation.to_account_info();
+        if !delegation.data_is_empty() {
+            Delegation::try_deserialize(&mut &delegation.try_borrow_data()?[..])?;
+            close_account(&delegation, &ctx.accounts.user.to_account_info())?;
+        }
+        let recovery = ctx.accounts.recovery.to_account_info();
+        if !recovery.data_is_empty() {
+            let initiator =
+                Recovery::try_deserialize(&mut &recovery.try_borrow_data()?[..])?.initiator;
+            let recovery_initiator = ctx
+                .accounts
+                .recovery_initiator
+                .as_ref()
+                .ok_or(WalletError::InsufficientAccounts)?;
+            require_keys_eq!(
+                recovery_initiator.key(),
+                initiator,
+                ErrorCode::ConstraintAddress
+            );
+            close_account(&recovery, &recovery_initiator.to_account_info())?;
+        }
+        Ok(())
+    }
+    pub fn pause_wallet<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PauseWallet<'info>>,
+    ) -> Result<()> {
+        // owners co-signing the pause are passed as (owner, wallet_auth) pairs
+        require_eq!(
+            ctx.remaining_accounts.len() % 2,
+            0,
+            WalletError::InsufficientAccounts
+        );
+        let wallet_key = ctx.accounts.wallet.key();
+        let mut signer_ids = vec![ctx.accounts.wallet_auth.id];
+        let account_info_iter = &mut ctx.remaining_accounts.iter();
+        let mut owner;
+        let mut wallet_auth;
+        while account_info_iter.len() > 0 {
+            owner = next_account_info(account_info_iter)?;
+            wallet_auth = Account::<WalletAuth>::try_from(next_account_info(account_info_iter)?)?;
+            require!(owner.is_signer, ErrorCode::AccountNotSigner);
+            require!(wallet_auth.is_current(), WalletError::AccountNotMigrated);
+            require_keys_eq!(
+                wallet_auth.owner,
+                owner.key(),
+                WalletError::InvalidWalletAuth
+            );
+            require_keys_eq!(
+                wallet_auth.wallet,
+                wallet_key,
+                WalletError::InvalidWalletAuth
+            );
+            if !signer_ids.contains(&wallet_auth.id) {
+                signer_ids.push(wallet_auth.id);
+            }
         }
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        require_gte!(
+            signer_ids.len(),
+            wallet.pause_threshold as usize,
+            WalletError::NotEnoughSigners
+        );
+        wallet.paused = 1;
+        Ok(())
+    }
+    pub fn delegate_vote(ctx: Context<DelegateVote>, delegate: Pubkey, expiry: i64) -> Result<()> {
+        require_keys_neq!(
+            delegate,
+            ctx.accounts.user.key(),
+            WalletError::InvalidDelegation
+        );
+        require_gt!(
+            expiry,
+            Clock::get()?.unix_timestamp,
+            WalletError::DelegationExpired
+        );
+        ctx.accounts.delegation.set_inner(Delegation {
+            wallet: ctx.accounts.wallet.key(),
+            delegator: ctx.accounts.user.key(),
+            delegate,
+            expiry,
+            version: ACCOUNT_VERSION,
+            reserved: [0; 32],
+        });
+        Ok(())
+    }
+    pub fn revoke_delegation(_ctx: Context<RevokeDelegation>) -> Result<()> {
         Ok(())
     }
     pub fn create_token_account(_ctx: Context<CreateTokenAccount>) -> Result<()> {
         Ok(())
     }
-    pub fn create_proposal(ctx: Context<CreateProposal>, proposal: ProposalType) -> Result<()> {
+    pub fn create_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateProposal<'info>>,
+        proposal: ProposalType,
+        keep_record: bool,
+        draft: bool,
+        auto_approve: bool,
+    ) -> Result<()> {
         match proposal {
             ProposalType::Transfer {
                 token_mint,
                 receive_account,
                 amount,
+                decimals,
             } => {
                 require!(amount > 0, WalletError::ZeroSendAmount);
-                let token_account = ctx.accounts.receive_account.as_ref().unwrap();
+                let token_account = ctx
+                    .accounts
+                    .receive_account
+                    .as_ref()
+                    .ok_or(WalletError::InsufficientAccounts)?;
                 require_keys_eq!(
                     receive_account,
                     token_account.key(),
                     WalletError::TokenAccountMismatch
                 );
                 require_keys_eq!(token_mint, token_account.mint, WalletError::MintMismatch);
+                let mint = ctx
+                    .accounts
+                    .mint
+                    .as_ref()
+                    .ok_or(WalletError::InsufficientAccounts)?;
+                require_keys_eq!(token_mint, mint.key(), WalletError::MintMismatch);
+                require_eq!(decimals, mint.decimals, WalletError::DecimalsMismatch);
+                check_token_extensions(mint, token_account, amount)?;
             } // duration should be atleast 10 minutes
             ProposalType::ChangeProposalLifetime { duration } => {
                 require!(duration >= 600, WalletError::TooShortDuration)
             }
+            ProposalType::ChangeOwnerWeight { weight, .. } => {
+                require!(weight > 0, WalletError::ZeroWeight)
+            }
+            ProposalType::ChangePauseThreshold { threshold } => {
+                require!(
+                    threshold > 0 && threshold as u16 <= ctx.accounts.wallet.load_current()?.owners,
+                    WalletError::InvalidPauseThreshold
+                )
+            }
+            // an empty guardian list with zero threshold disables recovery
+            ProposalType::SetGuardians {
+                ref guardians,
+                threshold,
+                recovery_delay,
+            } => {
+                require!(
+                    guardians.len() <= GuardianSet::MAX_GUARDIANS
+                        && threshold as usize <= guardians.len()
+                        && (threshold > 0 || guardians.is_empty()),
+                    WalletError::InvalidGuardians
+                );
+                for (pos, guardian) in guardians.iter().enumerate() {
+                    require!(
+                        !guardians[pos + 1..].contains(guardian),
+                        WalletError::InvalidGuardians
+                    );
+                }
+                require!(
+                    recovery_delay >= GuardianSet::MIN_RECOVERY_DELAY,
+                    WalletError::TooShortRecoveryDelay
+                );
+            }
+            ProposalType::BatchTransfer { ref transfers } => {
+                check_transfer_entries(transfers, ctx.remaining_accounts)?;
+            }
+            ProposalType::UpdateMetadata {
+                ref name,
+                ref description,
+                ref icon_uri,
+            } => {
+                if let Some(name) = name {
+                    require!(
+                        !name.is_empty() && name.len() < WalletConfig::MAX_NAME_LEN,
+                        WalletError::InvalidMetadata
+                    );
+                }
+                require!(
+                    description.as_ref().is_none_or(
+                        |description| description.len() <= WalletMetadata::MAX_DESCRIPTION_LEN
+                    ) && icon_uri
+                        .as_ref()
+                        .is_none_or(|icon_uri| icon_uri.len() <= WalletMetadata::MAX_ICON_URI_LEN),
+                    WalletError::InvalidMetadata
+                );
+            }
+            ProposalType::UpdateAddressBook {
+                ref add,
+                ref remove,
+            } => {
+                for (pos, entry) in add.iter().enumerate() {
+                    require!(
+                        !entry.name.is_empty()
+                            && entry.name.len() <= AddressBook::MAX_NAME_LEN
+                            && !add[pos + 1..]
+                                .iter()
+                                .any(|other| other.address == entry.address)
+                            && !remove.contains(&entry.address),
+                        WalletError::InvalidAddressBookEntry
+                    );
+                }
+            }
+            ProposalType::ChangeAddressBookMode {
+                mode: AddressBookMode::HigherThreshold,
+                unlisted_m,
+            } => {
+                let wallet = ctx.accounts.wallet.load_current()?;
+                require!(
+                    unlisted_m > wallet.m && unlisted_m <= wallet.n,
+                    WalletError::InvalidAddressBookMode
+                );
+            }
+            ProposalType::SetTransferTiers { ref tiers, .. } => {
+                let wallet = ctx.accounts.wallet.load_current()?;
+                require_gte!(
+                    TransferTiers::MAX_TIERS,
+                    tiers.len(),
+                    WalletError::InvalidTransferTiers
+                );
+                for (pos, tier) in tiers.iter().enumerate() {
+                    require!(
+                        tier.m > 0
+                            && tier.m <= wallet.n
+                            && (pos == 0 || tier.min_amount > tiers[pos - 1].min_amount),
+                        WalletError::InvalidTransferTiers
+                    );
+                }
+            }
+            ProposalType::SetTypeThreshold { proposal_type, m } => {
+                let wallet = ctx.accounts.wallet.load_current()?;
+                require!(
+                    (proposal_type as usize) < WalletConfig::MAX_PROPOSAL_TYPES && m <= wallet.n,
+                    WalletError::InvalidTypeThreshold
+                );
+            }
+            ProposalType::RecurringTransfer {
+                mint: token_mint,
+                receive_account,
+                amount,
+                interval,
+                count,
+            } => {
+                require!(amount > 0, WalletError::ZeroSendAmount);
+                require!(
+                    count > 0 && interval >= PaymentSchedule::MIN_INTERVAL,
+                    WalletError::InvalidSchedule
+                );
+                let token_account = ctx
+                    .accounts
+                    .receive_account
+                    .as_ref()
+                    .ok_or(WalletError::InsufficientAccounts)?;
+                require_keys_eq!(
+                    receive_account,
+                    token_account.key(),
+                    WalletError::TokenAccountMismatch
+                );
+                require_keys_eq!(token_mint, token_account.mint, WalletError::MintMismatch);
+                let mint = ctx
+                    .accounts
+                    .mint
+                    .as_ref()
+                    .ok_or(WalletError::InsufficientAccounts)?;
+                require_keys_eq!(token_mint, mint.key(), WalletError::MintMismatch);
+                check_token_extensions(mint, token_account, amount)?;
+            }
+            ProposalType::CreateVesting {
+                amount,
+                start,
+                cliff,
+                end,
+                ..
+            } => {
+                require!(amount > 0, WalletError::ZeroSendAmount);
+                require!(
+                    start < end && start <= cliff && cliff <= end,
+                    WalletError::InvalidVesting
+                );
+            }
+            ProposalType::OtcTrade {
+                give_amount,
+                want_amount,
+                ..
+            } => {
+                require!(
+                    give_amount > 0 && want_amount > 0,
+                    WalletError::ZeroSendAmount
+                );
+            }
+            ProposalType::CreateStake { lamports }
+            | ProposalType::WithdrawStake { lamports, .. } => {
+                require!(lamports > 0, WalletError::ZeroSendAmount)
+            }
             _ => (),
         }
+        let (mut wallet, owner_identities) =
+            WalletConfig::load_mut_with_owners(&ctx.accounts.wallet)?;
+        let current_time = Clock::get()?.unix_timestamp;
         ctx.accounts.proposal.set_inner(Proposal {
             wallet: ctx.accounts.wallet.key(),
+            index: wallet.proposal_count,
             proposer: ctx.accounts.user.key(),
+            config_version: wallet.config_version,
+            status: ProposalStatus::Active,
+            timestamps: ProposalTimestamps::default(),
+            keep_record,
+            votes: 0,
+            weight: 0,
+            executed_transfers: 0,
             proposal,
+            version: ACCOUNT_VERSION,
+            reserved: [0; 32],
         });
-        let mut vote_record = [0u8; 32];
-        let user_id = ctx.accounts.wallet_auth.id;
-        let mut vote_string = String::from("00000000");
-        vote_string.insert((user_id % 8) as usize, '1');
-        if user_id % 8 == 0 {
-            vote_string.pop();
+        wallet.proposal_count = wallet.proposal_count.checked_add(1).unwrap();
+        // drafts are not open for voting until they are activated
+        if draft {
+            ctx.accounts
+                .proposal
+                .set_status(ProposalStatus::Draft, current_time);
         } else {
-            vote_string.remove(0);
+            ctx.accounts
+                .proposal
+                .set_status(ProposalStatus::Active, current_time);
+        }
+        // the proposer's vote is only recorded if they approve the proposal on creation,
+        // on a draft they vote once it is activated
+        ctx.accounts.vote_count.load_init()?.version = ACCOUNT_VERSION;
+        let (mut vote_count, mut owner_snapshot, mut vote_record) =
+            VoteCount::load_mut_with_bitmaps(&ctx.accounts.vote_count)?;
+        if auto_approve && !draft {
+            bitmap::set(&mut vote_record, ctx.accounts.wallet_auth.id);
+            vote_count.votes = 1;
+            vote_count.weight = ctx.accounts.wallet_auth.weight as u64;
+        }
+        vote_count.proposed_time = current_time;
+        owner_snapshot.copy_from_slice(&owner_identities);
+        vote_count.threshold = proposal_threshold(
+            &wallet,
+            &ctx.accounts.proposal.proposal,
+            ctx.accounts.address_book.as_deref(),
+            ctx.accounts.transfer_tiers.as_deref(),
+        )?;
+        if !draft && vote_count.weight >= vote_count.threshold {
+            ctx.accounts
+                .proposal
+                .set_status(ProposalStatus::Approved, current_time);
         }
-        vote_record[(user_id / 8) as usize] = u8::from_str_radix(&vote_string, 2).unwrap();
-        ctx.accounts.vote_count.set_inner(VoteCount {
-            proposed_time: Clock::get()?.unix_timestamp,
-            votes: 1,
-            vote_record,
-        });
         Ok(())
     }
-    pub fn vote(ctx: Context<Voting>) -> Result<()> {
-        let user_id = ctx.accounts.wallet_auth.id;
-        let user_pos = (user_id % 8) as usize;
-        let vote_count = &mut ctx.accounts.vote_count;
-        let mut vote_string = format!("{:08b}", vote_count.vote_record[(user_id / 8) as usize]);
+    pub fn append_transfers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AppendTransfers<'info>>,
+        transfers: Vec<TransferEntry>,
+    ) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            proposal.status == ProposalStatus::Draft,
+            WalletError::ProposalNotDraft
+        );
+        match &mut proposal.proposal {
+            ProposalType::BatchTransfer {
+                transfers: proposal_transfers,
+            } => {
+                check_transfer_entries(&transfers, ctx.remaining_accounts)?;
+                proposal_transfers.extend(transfers);
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        Ok(())
+    }
+    pub fn activate_proposal(ctx: Context<ActivateProposal>) -> Result<()> {
+        let (wallet, owner_identities) = WalletConfig::load_with_owners(&ctx.accounts.wallet)?;
+        let proposal = &mut ctx.accounts.proposal;
+        let (mut vote_count, mut owner_snapshot, mut vote_record) =
+            VoteCount::load_mut_with_bitmaps(&ctx.accounts.vote_count)?;
+        require!(
+            proposal.status == ProposalStatus::Draft,
+            WalletError::ProposalNotDraft
+        );
+        // voting starts now, so the lifetime and the snapshot start from activation
+        let current_time = Clock::get()?.unix_timestamp;
+        proposal.config_version = wallet.config_version;
+        proposal.set_status(ProposalStatus::Active, current_time);
+        // the bitmaps are rewritten as the account may have been resized
+        vote_count.proposed_time = current_time;
+        owner_snapshot.copy_from_slice(&owner_identities);
+        vote_record.fill(0);
+        // transfers appended to the draft are checked against the address book here
+        vote_count.threshold = proposal_threshold(
+            &wallet,
+            &proposal.proposal,
+            ctx.accounts.address_book.as_deref(),
+            ctx.accounts.transfer_tiers.as_deref(),
+        )?;
+        if vote_count.weight >= vote_count.threshold {
+            proposal.set_status(ProposalStatus::Approved, current_time);
+        }
+        Ok(())
+    }
+    // a delegate can vote for its delegators without casting its own vote
+    pub fn vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Voting<'info>>,
+        include_own: bool,
+    ) -> Result<()> {
+        // votes of delegators are passed as (delegation, wallet_auth) pairs
+        require_eq!(
+            ctx.remaining_accounts.len() % 2,
+            0,
+            WalletError::InsufficientAccounts
+        );
+        require!(
+            include_own || !ctx.remaining_accounts.is_empty(),
+            WalletError::InsufficientAccounts
+        );
+        require!(
+            ctx.accounts.proposal.is_open(),
+            WalletError::ProposalNotOpen
+        );
         require_eq!(
-            &vote_string[user_pos..user_pos + 1],
-            "0",
-            WalletError::AlreadyVoted
-        );
-        vote_string.replace_range(user_pos..user_pos + 1, "1");
-        vote_count.vote_record[(user_id / 8) as usize] =
-            u8::from_str_radix(&vote_string, 2).unwrap();
-        vote_count.votes = vote_count.votes.checked_add(1).unwrap();
+            ctx.accounts.proposal.config_version,
+            ctx.accounts.wallet.load_current()?.config_version,
+            WalletError::StaleProposal
+        );
+        let wallet_key = ctx.accounts.wallet.key();
+        let user_key = ctx.accounts.user.key();
+        let current_time = Clock::get()?.unix_timestamp;
+        let mut voters = Vec::new();
+        if include_own {
+            voters.push((
+                ctx.accounts.wallet_auth.id,
+                ctx.accounts.wallet_auth.weight,
+                ctx.accounts.wallet_auth.added_time,
+            ));
+        }
+        let account_info_iter = &mut ctx.remaining_accounts.iter();
+        let mut delegation;
+        let mut wallet_auth;
+        while account_info_iter.len() > 0 {
+            delegation = Account::<Delegation>::try_from(next_account_info(account_info_iter)?)?;
+            wallet_auth = Account::<WalletAuth>::try_from(next_account_info(account_info_iter)?)?;
+            require!(
+                delegation.is_current() && wallet_auth.is_current(),
+                WalletError::AccountNotMigrated
+            );
+            require_keys_eq!(
+                delegation.wallet,
+                wallet_key,
+                WalletError::InvalidDelegation
+            );
+            require_keys_eq!(
+                delegation.delegate,
+                user_key,
+                WalletError::InvalidDelegation
+            );
+            require_gt!(
+                delegation.expiry,
+                current_time,
+                WalletError::DelegationExpired
+            );
+            require_keys_eq!(
+                wallet_auth.owner,
+                delegation.delegator,
+                WalletError::InvalidWalletAuth
+            );
+            require_keys_eq!(
+                wallet_auth.wallet,
+                wallet_key,
+                WalletError::InvalidWalletAuth
+            );
+            voters.push((wallet_auth.id, wallet_auth.weight, wallet_auth.added_time));
+        }
+
+        // owners who already voted are skipped, the vote fails only if nothing was recorded
+        let (mut vote_count, owner_snapshot, mut vote_record) =
+            VoteCount::load_mut_with_bitmaps(&ctx.accounts.vote_count)?;
+        let mut recorded = false;
+        for (user_id, weight, added_time) in voters {
+            // only owners present in the snapshot can vote, an id may have been reused since
+            require!(
+                bitmap::is_set(&owner_snapshot, user_id) && added_time <= vote_count.proposed_time,
+                WalletError::NotInSnapshot
+            );
+            if bitmap::is_set(&vote_record, user_id) {
+                continue;
+            }
+            bitmap::set(&mut vote_record, user_id);
+            vote_count.votes = vote_count.votes.checked_add(1).unwrap();
+            vote_count.weight = vote_count.weight.checked_add(weight as u64).unwrap();
+            recorded = true;
+        }
+        require!(recorded, WalletError::AlreadyVoted);
+        let proposal = &mut ctx.accounts.proposal;
+        if proposal.status == ProposalStatus::Active && vote_count.weight >= vote_count.threshold {
+            proposal.set_status(ProposalStatus::Approved, current_time);
+        }
         Ok(())
     }
     pub fn revoke_vote(ctx: Context<Voting>) -> Result<()> {
-        let user_id = ctx.accounts.wallet_auth.id;
-        let user_pos = (user_id % 8) as usize;
-        let vote_count = &mut ctx.accounts.vote_count;
-        let mut vote_string = format!("{:08b}", vote_count.vote_record[(user_id / 8) as usize]);
-        require_eq!(
-            &vote_string[user_pos..user_pos + 1],
-            "1",
-            WalletError::NotVoted
+        require!(
+            ctx.accounts.proposal.is_open(),
+            WalletError::ProposalNotOpen
         );
-        vote_string.replace_range(user_pos..user_pos + 1, "0");
-        vote_count.vote_record[(user_id / 8) as usize] =
-            u8::from_str_radix(&vote_string, 2).unwrap();
+        let user_id = ctx.accounts.wallet_auth.id;
+        let (mut vote_count, _, mut vote_record) =
+            VoteCount::load_mut_with_bitmaps(&ctx.accounts.vote_count)?;
+        require!(bitmap::is_set(&vote_record, user_id), WalletError::NotVoted);
+        bitmap::clear(&mut vote_record, user_id);
         vote_count.votes = vote_count.votes.checked_sub(1).unwrap();
+        vote_count.weight = vote_count
+            .weight
+            .checked_sub(ctx.accounts.wallet_auth.weight as u64)
+            .unwrap();
+        let proposal = &mut ctx.accounts.proposal;
+        if proposal.status == ProposalStatus::Approved && vote_count.weight < vote_count.threshold {
+            proposal.status = ProposalStatus::Active;
+            proposal.timestamps.approved = 0;
+        }
         Ok(())
     }
-    pub fn transfer(ctx: Context<TransferFunds>) -> Result<()> {
-        let wallet = &ctx.accounts.wallet;
-        let vote_count = &ctx.accounts.vote_count;
-        if Clock::get()?.unix_timestamp >= vote_count.proposed_time + wallet.proposal_lifetime {
-            return Ok(());
-        }
-        require_gte!(
-            vote_count.votes,
-            (wallet.owners * wallet.m) / wallet.n,
-            WalletError::NotEnoughVotes
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        // drafts can be dropped before they are ever activated
+        require!(
+            ctx.accounts.proposal.is_open()
+                || ctx.accounts.proposal.status == ProposalStatus::Draft,
+            WalletError::ProposalNotOpen
         );
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.user.to_account_info(),
+            ProposalStatus::Cancelled,
+        )
+    }
+    pub fn close_proposal(ctx: Context<CloseProposal>) -> Result<()> {
+        require!(
+            !ctx.accounts.proposal.is_open()
+                && ctx.accounts.proposal.status != ProposalStatus::Draft,
+            WalletError::ProposalNotFinalised
+        );
+        Ok(())
+    }
+    // records an expired or stale proposal as such, the accounts an execution would create
+    // are never created for it
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        require!(
+            ctx.accounts.proposal.is_open(),
+            WalletError::ProposalNotOpen
+        );
+        let status = final_status(&ctx.accounts.proposal, &wallet, &vote_count)?
+            .ok_or(WalletError::ProposalStillValid)?;
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            status,
+        )
+    }
+    pub fn transfer<'info>(ctx: Context<'_, '_, '_, 'info, TransferFunds<'info>>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
         match ctx.accounts.proposal.proposal {
             ProposalType::Transfer {
                 token_mint,
                 receive_account,
                 amount,
+                decimals,
             } => {
-                msg!("3");
                 let cpi_program = ctx.accounts.token_program.to_account_info();
-                msg!("4");
                 let from_account = &ctx.accounts.send_account;
-                msg!("5");
                 let to_account = &ctx.accounts.receive_account;
-                msg!("6");
                 require_keys_eq!(
                     from_account.mint,
                     token_mint,
                     WalletError::IncorrectTokenAccount
                 );
-                msg!("7");
                 require_keys_eq!(
                     to_account.key(),
                     receive_account,
                     WalletError::IncorrectTokenAccount
                 );
-                msg!("8");
-                let cpi_context = CpiContext::new(
-                    cpi_program,
-                    Transfer {
-                        from: from_account.to_account_info(),
-                        to: to_account.to_account_info(),
-                        authority: ctx.accounts.wallet_authority.to_account_info(),
-                    },
+                require_keys_eq!(
+                    ctx.accounts.mint.key(),
+                    token_mint,
+                    WalletError::MintMismatch
                 );
-                msg!("9");
-                token::transfer(
-                    cpi_context.with_signer(&[&[
-                        "authority".as_bytes().as_ref(),
-                        wallet.key().as_ref(),
-                        &[*ctx.bumps.get("wallet_authority").unwrap()],
-                    ]]),
+                // remaining accounts carry the extra accounts needed by a transfer hook, if any
+                spl_token_2022::onchain::invoke_transfer_checked(
+                    cpi_program.key,
+                    from_account.to_account_info(),
+                    ctx.accounts.mint.to_account_info(),
+                    to_account.to_account_info(),
+                    ctx.accounts.wallet_authority.to_account_info(),
+                    ctx.remaining_accounts,
                     amount,
+                    decimals,
+                    &[&[
+                        "authority".as_bytes(),
+                        ctx.accounts.wallet.key().as_ref(),
+                        &[ctx.bumps.wallet_authority],
+                    ]],
                 )?;
-                msg!("10");
             }
             _ => return err!(WalletError::ProposalInstructionMismatch),
         }
-        Ok(())
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
     }
 
-    pub fn add_owner(ctx: Context<AddOwner>) -> Result<()> {
-        msg!("1");
-        let wallet = &ctx.accounts.wallet;
-        let vote_count = &ctx.accounts.vote_count;
-        if Clock::get()?.unix_timestamp >= vote_count.proposed_time + wallet.proposal_lifetime {
+    // the transfers run in chunks over several transactions, hook_accounts holds the number
+    // of transfer hook accounts of each transfer in this chunk
+    pub fn batch_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchTransferFunds<'info>>,
+        hook_accounts: Vec<u8>,
+    ) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        let proposal = &mut ctx.accounts.proposal;
+        let executed = proposal.executed_transfers as usize;
+        let finished = match &proposal.proposal {
+            ProposalType::BatchTransfer { transfers } => {
+                require!(
+                    !hook_accounts.is_empty() && executed + hook_accounts.len() <= transfers.len(),
+                    WalletError::InvalidBatchChunk
+                );
+                // accounts are passed as (send account, mint, receive account) triples followed
+                // by the hook accounts of the transfer, for the next transfers in order
+                require_eq!(
+                    ctx.remaining_accounts.len(),
+                    hook_accounts
+                        .iter()
+                        .map(|count| 3 + *count as usize)
+                        .sum::<usize>(),
+                    WalletError::InsufficientAccounts
+                );
+                let authority = ctx.accounts.wallet_authority.to_account_info();
+                let mut accounts = ctx.remaining_accounts;
+                for (transfer, count) in transfers[executed..].iter().zip(&hook_accounts) {
+                    let (transfer_accounts, rest) = accounts.split_at(3 + *count as usize);
+                    accounts = rest;
+                    let send_account =
+                        InterfaceAccount::<TokenAccount>::try_from(&transfer_accounts[0])?;
+                    let mint = &transfer_accounts[1];
+                    let receive_account = &transfer_accounts[2];
+                    require_keys_eq!(
+                        send_account.owner,
+                        authority.key(),
+                        WalletError::IncorrectTokenAccount
+                    );
+                    require_keys_eq!(
+                        send_account.mint,
+                        transfer.token_mint,
+                        WalletError::IncorrectTokenAccount
+                    );
+                    require_keys_eq!(mint.key(), transfer.token_mint, WalletError::MintMismatch);
+                    require_keys_eq!(
+                        receive_account.key(),
+                        transfer.receive_account,
+                        WalletError::IncorrectTokenAccount
+                    );
+                    spl_token_2022::onchain::invoke_transfer_checked(
+                        ctx.accounts.token_program.key,
+                        send_account.to_account_info(),
+                        mint.clone(),
+                        receive_account.clone(),
+                        authority.clone(),
+                        &transfer_accounts[3..],
+                        transfer.amount,
+                        transfer.decimals,
+                        &[&[
+                            "authority".as_bytes(),
+                            ctx.accounts.wallet.key().as_ref(),
+                            &[ctx.bumps.wallet_authority],
+                        ]],
+                    )?;
+                }
+                executed + hook_accounts.len() == transfers.len()
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        };
+        proposal.executed_transfers += hook_accounts.len() as u32;
+        if !finished {
             return Ok(());
         }
+        drop(vote_count);
+        drop(wallet);
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )?;
+        // the vote count stays open until the last chunk
+        ctx.accounts
+            .vote_count
+            .close(ctx.accounts.proposer.to_account_info())
+    }
+
+    pub fn add_owner(ctx: Context<AddOwner>) -> Result<()> {
+        let (mut wallet, mut owner_identities) =
+            WalletConfig::load_mut_with_owners(&ctx.accounts.wallet)?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::AddOwner { user } => {
+                require!(
+                    wallet.owners < WalletConfig::MAX_OWNERS,
+                    WalletError::MaxOwners
+                );
+                // find available id and assign it to the new owner, the bitmap was grown
+                // by the realloc constraint if every id was taken
+                let id = bitmap::first_unset(&owner_identities).unwrap();
+                bitmap::set(&mut owner_identities, id);
+                wallet.owners = wallet.owners.checked_add(1).unwrap();
+                wallet.total_weight = wallet.total_weight.checked_add(1).unwrap();
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+                ctx.accounts.wallet_auth.set_inner(WalletAuth {
+                    owner: user,
+                    wallet: ctx.accounts.wallet.key(),
+                    id,
+                    added_time: Clock::get()?.unix_timestamp,
+                    weight: 1,
+                    version: ACCOUNT_VERSION,
+                    reserved: [0; 32],
+                });
+                finalize_proposal(
+                    &mut ctx.accounts.proposal,
+                    &ctx.accounts.vote_count,
+                    ctx.accounts.proposer.to_account_info(),
+                    ProposalStatus::Executed,
+                )
+            }
+            _ => err!(WalletError::ProposalInstructionMismatch),
+        }
+    }
+    pub fn change_lifetime(ctx: Context<ChangeLifetime>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::ChangeProposalLifetime { duration } => {
+                wallet.proposal_lifetime = duration;
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn set_guardians(ctx: Context<SetGuardians>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match &ctx.accounts.proposal.proposal {
+            ProposalType::SetGuardians {
+                guardians,
+                threshold,
+                recovery_delay,
+            } => {
+                ctx.accounts.guardian_set.set_inner(GuardianSet {
+                    wallet: ctx.accounts.wallet.key(),
+                    guardians: guardians.clone(),
+                    threshold: *threshold,
+                    recovery_delay: *recovery_delay,
+                    set_time: Clock::get()?.unix_timestamp,
+                    version: ACCOUNT_VERSION,
+                    reserved: [0; 32],
+                });
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+
+    pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_owner: Pubkey) -> Result<()> {
+        let guardian_pos = ctx
+            .accounts
+            .guardian_set
+            .position(&ctx.accounts.user.key())
+            .ok_or(WalletError::NotGuardian)?;
+        ctx.accounts.recovery.set_inner(Recovery {
+            wallet: ctx.accounts.wallet.key(),
+            owner: ctx.accounts.wallet_auth.owner,
+            new_owner,
+            initiator: ctx.accounts.user.key(),
+            initiated_time: Clock::get()?.unix_timestamp,
+            approvals: 1 << guardian_pos,
+            version: ACCOUNT_VERSION,
+            reserved: [0; 32],
+        });
+        Ok(())
+    }
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+        let guardian_set = &ctx.accounts.guardian_set;
+        let recovery = &mut ctx.accounts.recovery;
         require_gte!(
-            vote_count.votes,
-            (wallet.owners * wallet.m) / wallet.n,
-            WalletError::NotEnoughVotes
+            recovery.initiated_time,
+            guardian_set.set_time,
+            WalletError::StaleRecovery
+        );
+        let guardian_pos = guardian_set
+            .position(&ctx.accounts.user.key())
+            .ok_or(WalletError::NotGuardian)?;
+        require!(
+            recovery.approvals & (1 << guardian_pos) == 0,
+            WalletError::AlreadyApproved
         );
-        msg!("2");
+        recovery.approvals |= 1 << guardian_pos;
+        Ok(())
+    }
+    // any owner can veto, the recovery is closed by the accounts constraint
+    pub fn veto_recovery(_ctx: Context<VetoRecovery>) -> Result<()> {
+        Ok(())
+    }
+    // guardians can cancel a recovery, once the guardian set changed anyone can close it
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        let guardian_set = &ctx.accounts.guardian_set;
+        if ctx.accounts.recovery.initiated_time >= guardian_set.set_time {
+            require!(
+                guardian_set.position(&ctx.accounts.user.key()).is_some(),
+                WalletError::NotGuardian
+            );
+        }
+        Ok(())
+    }
+    pub fn execute_recovery(ctx: Context<ExecuteRecovery>) -> Result<()> {
+        let guardian_set = &ctx.accounts.guardian_set;
+        let recovery = &ctx.accounts.recovery;
+        require_gte!(
+            recovery.initiated_time,
+            guardian_set.set_time,
+            WalletError::StaleRecovery
+        );
+        require_gte!(
+            Clock::get()?.unix_timestamp,
+            recovery.initiated_time + guardian_set.recovery_delay,
+            WalletError::RecoveryDelayNotPassed
+        );
+        require_gte!(
+            recovery.approvals.count_ones(),
+            guardian_set.threshold as u32,
+            WalletError::NotEnoughApprovals
+        );
+        // the recovered owner keeps the id, same as with rotate_owner_key
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+        let wallet_auth = &ctx.accounts.wallet_auth;
+        ctx.accounts.new_wallet_auth.set_inner(WalletAuth {
+            owner: recovery.new_owner,
+            wallet: wallet_auth.wallet,
+            id: wallet_auth.id,
+            added_time: wallet_auth.added_time,
+            weight: wallet_auth.weight,
+            version: ACCOUNT_VERSION,
+            reserved: [0; 32],
+        });
+        Ok(())
+    }
+    pub fn change_pause_threshold(ctx: Context<ChangeWalletState>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
         match ctx.accounts.proposal.proposal {
-            ProposalType::AddOwner { user } => {
-                let wallet = &mut ctx.accounts.wallet;
-                require!(wallet.owners < 255, WalletError::MaxOwners);
-                // find available id and assign it to the new owner
-                let mut byte_count = 0;
-                let mut byte;
-                let mut owner_str;
-                loop {
-                    byte = wallet.owner_identities[byte_count];
-                    owner_str = format!("{:08b}", byte);
-                    for pos in 0..8 {
-                        if &owner_str[pos..pos + 1] == "0" {
-                            owner_str.replace_range(pos..pos + 1, "1");
-                            wallet.owner_identities[byte_count] =
-                                u8::from_str_radix(&owner_str, 2).unwrap();
-                            wallet.owners = wallet.owners.checked_add(1).unwrap();
-                            ctx.accounts.wallet_auth.set_inner(WalletAuth {
-                                owner: user,
-                                wallet: wallet.key(),
-                                id: (byte_count * 8 + pos) as u8,
-                                added_time: Clock::get()?.unix_timestamp,
-                            });
-                            return Ok(());
-                        }
+            ProposalType::ChangePauseThreshold { threshold } => {
+                wallet.pause_threshold = threshold;
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn unpause(ctx: Context<ChangeWalletState>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::Unpause => {
+                wallet.paused = 0;
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn change_owner_weight(ctx: Context<ChangeOwnerWeight>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::ChangeOwnerWeight { weight, .. } => {
+                let wallet_auth = &mut ctx.accounts.wallet_auth;
+                wallet.total_weight = wallet
+                    .total_weight
+                    .checked_sub(wallet_auth.weight as u64)
+                    .unwrap()
+                    .checked_add(weight as u64)
+                    .unwrap();
+                wallet_auth.weight = weight;
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn update_metadata(ctx: Context<UpdateMetadata>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match &ctx.accounts.proposal.proposal {
+            // metadata is descriptive only, so the config version is left as it is
+            ProposalType::UpdateMetadata {
+                name,
+                description,
+                icon_uri,
+            } => {
+                if let Some(name) = name {
+                    wallet.name = [0; WalletConfig::MAX_NAME_LEN];
+                    wallet.name[..name.len()].copy_from_slice(name.as_bytes());
+                }
+                let metadata = &mut ctx.accounts.metadata;
+                metadata.wallet = ctx.accounts.wallet.key();
+                metadata.version = ACCOUNT_VERSION;
+                if let Some(description) = description {
+                    metadata.description = description.clone();
+                }
+                if let Some(icon_uri) = icon_uri {
+                    metadata.icon_uri = icon_uri.clone();
+                }
+                resize_account(
+                    &metadata.to_account_info(),
+                    WalletMetadata::space(&metadata.description, &metadata.icon_uri),
+                    &ctx.accounts.payer.to_account_info(),
+                )?;
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn update_address_book(ctx: Context<UpdateAddressBook>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match &ctx.accounts.proposal.proposal {
+            ProposalType::UpdateAddressBook { add, remove } => {
+                let address_book = &mut ctx.accounts.address_book;
+                address_book.wallet = ctx.accounts.wallet.key();
+                address_book.version = ACCOUNT_VERSION;
+                address_book
+                    .entries
+                    .retain(|entry| !remove.contains(&entry.address));
+                for entry in add {
+                    match address_book
+                        .entries
+                        .iter_mut()
+                        .find(|existing| existing.address == entry.address)
+                    {
+                        Some(existing) => existing.name = entry.name.clone(),
+                        None => address_book.entries.push(entry.clone()),
                     }
-                    byte_count += 1;
                 }
+                require_gte!(
+                    AddressBook::MAX_ENTRIES,
+                    address_book.entries.len(),
+                    WalletError::AddressBookFull
+                );
+                resize_account(
+                    &address_book.to_account_info(),
+                    AddressBook::space(&address_book.entries),
+                    &ctx.accounts.payer.to_account_info(),
+                )?;
+                // open transfer proposals were checked against the old address book
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn change_address_book_mode(ctx: Context<ChangeWalletState>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::ChangeAddressBookMode { mode, unlisted_m } => {
+                wallet.address_book_mode = mode as u8;
+                wallet.unlisted_m = unlisted_m;
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn set_transfer_tiers(ctx: Context<SetTransferTiers>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match &ctx.accounts.proposal.proposal {
+            ProposalType::SetTransferTiers { mint, tiers } => {
+                let transfer_tiers = &mut ctx.accounts.transfer_tiers;
+                transfer_tiers.wallet = ctx.accounts.wallet.key();
+                transfer_tiers.version = ACCOUNT_VERSION;
+                transfer_tiers
+                    .mints
+                    .retain(|mint_tiers| mint_tiers.mint != *mint);
+                if !tiers.is_empty() {
+                    transfer_tiers.mints.push(MintTiers {
+                        mint: *mint,
+                        tiers: tiers.clone(),
+                    });
+                }
+                require_gte!(
+                    TransferTiers::MAX_MINTS,
+                    transfer_tiers.mints.len(),
+                    WalletError::TooManyTierMints
+                );
+                resize_account(
+                    &transfer_tiers.to_account_info(),
+                    TransferTiers::space(&transfer_tiers.mints),
+                    &ctx.accounts.payer.to_account_info(),
+                )?;
+                wallet.transfer_tiers = !transfer_tiers.mints.is_empty() as u8;
+                // open transfer proposals were created with the old tiers
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
             }
             _ => return err!(WalletError::ProposalInstructionMismatch),
         }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
     }
-    pub fn change_lifetime(ctx: Context<ChangeLifetime>) -> Result<()> {
-        msg!("1");
-        let wallet = &ctx.accounts.wallet;
-        let vote_count = &ctx.accounts.vote_count;
-        if Clock::get()?.unix_timestamp >= vote_count.proposed_time + wallet.proposal_lifetime {
-            return Ok(());
+    pub fn set_type_threshold(ctx: Context<ChangeWalletState>) -> Result<()> {
+        let mut wallet = ctx.accounts.wallet.load_current_mut()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::SetTypeThreshold { proposal_type, m } => {
+                wallet.type_m[proposal_type as usize] = m;
+                wallet.config_version = wallet.config_version.checked_add(1).unwrap();
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn start_recurring_transfer(ctx: Context<StartRecurringTransfer>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        let current_time = Clock::get()?.unix_timestamp;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::RecurringTransfer {
+                mint,
+                receive_account,
+                amount,
+                interval,
+                count,
+            } => {
+                ctx.accounts.schedule.set_inner(PaymentSchedule {
+                    wallet: ctx.accounts.wallet.key(),
+                    payer: ctx.accounts.payer.key(),
+                    mint,
+                    receive_account,
+                    amount,
+                    interval,
+                    next_payment: current_time,
+                    remaining: count,
+                    version: ACCOUNT_VERSION,
+                    reserved: [0; 32],
+                });
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
         }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    // anyone can pay a due instalment, missed instalments can be paid one after another
+    pub fn crank_payment<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrankPayment<'info>>,
+    ) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        require!(!wallet.is_paused(), WalletError::WalletPaused);
+        let schedule = &mut ctx.accounts.schedule;
         require_gte!(
-            vote_count.votes,
-            (wallet.owners * wallet.m) / wallet.n,
-            WalletError::NotEnoughVotes
+            Clock::get()?.unix_timestamp,
+            schedule.next_payment,
+            WalletError::PaymentNotDue
         );
-        msg!("2");
+        // remaining accounts carry the extra accounts needed by a transfer hook, if any
+        spl_token_2022::onchain::invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.send_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.receive_account.to_account_info(),
+            ctx.accounts.wallet_authority.to_account_info(),
+            ctx.remaining_accounts,
+            schedule.amount,
+            ctx.accounts.mint.decimals,
+            &[&[
+                "authority".as_bytes(),
+                ctx.accounts.wallet.key().as_ref(),
+                &[ctx.bumps.wallet_authority],
+            ]],
+        )?;
+        schedule.remaining -= 1;
+        schedule.next_payment = schedule
+            .next_payment
+            .checked_add(schedule.interval)
+            .unwrap();
+        if schedule.remaining == 0 {
+            schedule.close(ctx.accounts.schedule_payer.to_account_info())?;
+        }
+        Ok(())
+    }
+    pub fn cancel_recurring_transfer(ctx: Context<CancelRecurringTransfer>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
         match ctx.accounts.proposal.proposal {
-            ProposalType::ChangeProposalLifetime { duration } => {
-                ctx.accounts.wallet.proposal_lifetime = duration;
+            ProposalType::CancelRecurringTransfer { schedule } => {
+                require_keys_eq!(
+                    schedule,
+                    ctx.accounts.schedule.key(),
+                    WalletError::ProposalInstructionMismatch
+                );
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn create_vesting<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateVesting<'info>>,
+    ) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::CreateVesting {
+                mint,
+                beneficiary,
+                amount,
+                start,
+                cliff,
+                end,
+            } => {
+                require_keys_eq!(ctx.accounts.mint.key(), mint, WalletError::MintMismatch);
+                // remaining accounts carry the extra accounts needed by a transfer hook, if any
+                spl_token_2022::onchain::invoke_transfer_checked(
+                    ctx.accounts.token_program.key,
+                    ctx.accounts.send_account.to_account_info(),
+                    ctx.accounts.mint.to_account_info(),
+                    ctx.accounts.escrow.to_account_info(),
+                    ctx.accounts.wallet_authority.to_account_info(),
+                    ctx.remaining_accounts,
+                    amount,
+                    ctx.accounts.mint.decimals,
+                    &[&[
+                        "authority".as_bytes(),
+                        ctx.accounts.wallet.key().as_ref(),
+                        &[ctx.bumps.wallet_authority],
+                    ]],
+                )?;
+                // the escrow balance is vested, a transfer fee may have been withheld
+                ctx.accounts.escrow.reload()?;
+                ctx.accounts.vesting.set_inner(Vesting {
+                    wallet: ctx.accounts.wallet.key(),
+                    proposal: ctx.accounts.proposal.key(),
+                    beneficiary,
+                    mint,
+                    escrow: ctx.accounts.escrow.key(),
+                    payer: ctx.accounts.payer.key(),
+                    amount: ctx.accounts.escrow.amount,
+                    claimed: 0,
+                    start,
+                    cliff,
+                    end,
+                    bump: ctx.bumps.vesting,
+                    version: ACCOUNT_VERSION,
+                    reserved: [0; 32],
+                });
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn claim_vested<'info>(ctx: Context<'_, '_, '_, 'info, ClaimVested<'info>>) -> Result<()> {
+        require!(
+            !ctx.accounts.wallet.load_current()?.is_paused(),
+            WalletError::WalletPaused
+        );
+        let vesting = &mut ctx.accounts.vesting;
+        let claimable = vesting.vested(Clock::get()?.unix_timestamp) - vesting.claimed;
+        require_gt!(claimable, 0, WalletError::NothingToClaim);
+        // remaining accounts carry the extra accounts needed by a transfer hook, if any
+        spl_token_2022::onchain::invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.receive_account.to_account_info(),
+            vesting.to_account_info(),
+            ctx.remaining_accounts,
+            claimable,
+            ctx.accounts.mint.decimals,
+            &[&[
+                "vesting".as_bytes(),
+                vesting.wallet.as_ref(),
+                vesting.proposal.as_ref(),
+                &[vesting.bump],
+            ]],
+        )?;
+        vesting.claimed += claimable;
+        if vesting.claimed == vesting.amount {
+            close_vesting(
+                vesting,
+                &ctx.accounts.escrow,
+                &ctx.accounts.mint,
+                &ctx.accounts.token_program,
+                ctx.accounts.payer.to_account_info(),
+            )?;
+        }
+        Ok(())
+    }
+    pub fn clawback_vesting<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClawbackVesting<'info>>,
+    ) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        let current_time = Clock::get()?.unix_timestamp;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::ClawbackVesting { vesting } => {
+                require_keys_eq!(
+                    vesting,
+                    ctx.accounts.vesting.key(),
+                    WalletError::ProposalInstructionMismatch
+                );
+                let vesting = &mut ctx.accounts.vesting;
+                let vested = vesting.vested(current_time);
+                let unvested = vesting.amount - vested;
+                if unvested > 0 {
+                    // remaining accounts carry the extra accounts needed by a transfer hook, if any
+                    spl_token_2022::onchain::invoke_transfer_checked(
+                        ctx.accounts.token_program.key,
+                        ctx.accounts.escrow.to_account_info(),
+                        ctx.accounts.mint.to_account_info(),
+                        ctx.accounts.receive_account.to_account_info(),
+                        vesting.to_account_info(),
+                        ctx.remaining_accounts,
+                        unvested,
+                        ctx.accounts.mint.decimals,
+                        &[&[
+                            "vesting".as_bytes(),
+                            vesting.wallet.as_ref(),
+                            vesting.proposal.as_ref(),
+                            &[vesting.bump],
+                        ]],
+                    )?;
+                }
+                // the beneficiary can still claim what vested until now
+                vesting.amount = vested;
+                vesting.start = current_time.min(vesting.start);
+                vesting.cliff = current_time;
+                vesting.end = current_time;
+                if vesting.claimed == vesting.amount {
+                    close_vesting(
+                        vesting,
+                        &ctx.accounts.escrow,
+                        &ctx.accounts.mint,
+                        &ctx.accounts.token_program,
+                        ctx.accounts.payer.to_account_info(),
+                    )?;
+                }
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn escrow_otc_trade<'info>(
+        ctx: Context<'_, '_, '_, 'info, EscrowOtcTrade<'info>>,
+    ) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::OtcTrade {
+                give_mint,
+                give_amount,
+                want_mint,
+                want_amount,
+                counterparty,
+            } => {
+                require_keys_eq!(
+                    ctx.accounts.give_mint.key(),
+                    give_mint,
+                    WalletError::MintMismatch
+                );
+                // remaining accounts carry the extra accounts needed by a transfer hook, if any
+                spl_token_2022::onchain::invoke_transfer_checked(
+                    ctx.accounts.token_program.key,
+                    ctx.accounts.send_account.to_account_info(),
+                    ctx.accounts.give_mint.to_account_info(),
+                    ctx.accounts.escrow.to_account_info(),
+                    ctx.accounts.wallet_authority.to_account_info(),
+                    ctx.remaining_accounts,
+                    give_amount,
+                    ctx.accounts.give_mint.decimals,
+                    &[&[
+                        "authority".as_bytes(),
+                        ctx.accounts.wallet.key().as_ref(),
+                        &[ctx.bumps.wallet_authority],
+                    ]],
+                )?;
+                ctx.accounts.trade.set_inner(OtcTrade {
+                    wallet: ctx.accounts.wallet.key(),
+                    proposal: ctx.accounts.proposal.key(),
+                    payer: ctx.accounts.payer.key(),
+                    counterparty,
+                    give_mint,
+                    want_mint,
+                    escrow: ctx.accounts.escrow.key(),
+                    want_amount,
+                    bump: ctx.bumps.trade,
+                    version: ACCOUNT_VERSION,
+                    reserved: [0; 32],
+                });
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    // the counterparty pays the want side and receives the escrowed give side in one go
+    pub fn complete_otc_trade<'info>(
+        ctx: Context<'_, '_, '_, 'info, CompleteOtcTrade<'info>>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.wallet.load_current()?.is_paused(),
+            WalletError::WalletPaused
+        );
+        let trade = &ctx.accounts.trade;
+        let trade_seeds: &[&[u8]] = &[
+            "otc_trade".as_bytes(),
+            trade.wallet.as_ref(),
+            trade.proposal.as_ref(),
+            &[trade.bump],
+        ];
+        // remaining accounts carry the extra accounts needed by the transfer hooks of
+        // both mints, if any, each transfer picks the ones it needs
+        spl_token_2022::onchain::invoke_transfer_checked(
+            ctx.accounts.want_token_program.key,
+            ctx.accounts.counterparty_send_account.to_account_info(),
+            ctx.accounts.want_mint.to_account_info(),
+            ctx.accounts.receive_account.to_account_info(),
+            ctx.accounts.counterparty.to_account_info(),
+            ctx.remaining_accounts,
+            trade.want_amount,
+            ctx.accounts.want_mint.decimals,
+            &[],
+        )?;
+        spl_token_2022::onchain::invoke_transfer_checked(
+            ctx.accounts.give_token_program.key,
+            ctx.accounts.escrow.to_account_info(),
+            ctx.accounts.give_mint.to_account_info(),
+            ctx.accounts.counterparty_receive_account.to_account_info(),
+            trade.to_account_info(),
+            ctx.remaining_accounts,
+            ctx.accounts.escrow.amount,
+            ctx.accounts.give_mint.decimals,
+            &[trade_seeds],
+        )?;
+        close_escrow(
+            &ctx.accounts.give_token_program,
+            &ctx.accounts.escrow,
+            &ctx.accounts.give_mint,
+            ctx.accounts.payer.to_account_info(),
+            trade.to_account_info(),
+            &[trade_seeds],
+        )
+    }
+    pub fn cancel_otc_trade<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelOtcTrade<'info>>,
+    ) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::CancelOtcTrade { trade } => {
+                require_keys_eq!(
+                    trade,
+                    ctx.accounts.trade.key(),
+                    WalletError::ProposalInstructionMismatch
+                );
+                let trade = &ctx.accounts.trade;
+                let trade_seeds: &[&[u8]] = &[
+                    "otc_trade".as_bytes(),
+                    trade.wallet.as_ref(),
+                    trade.proposal.as_ref(),
+                    &[trade.bump],
+                ];
+                // remaining accounts carry the extra accounts needed by a transfer hook, if any
+                spl_token_2022::onchain::invoke_transfer_checked(
+                    ctx.accounts.token_program.key,
+                    ctx.accounts.escrow.to_account_info(),
+                    ctx.accounts.give_mint.to_account_info(),
+                    ctx.accounts.receive_account.to_account_info(),
+                    trade.to_account_info(),
+                    ctx.remaining_accounts,
+                    ctx.accounts.escrow.amount,
+                    ctx.accounts.give_mint.decimals,
+                    &[trade_seeds],
+                )?;
+                close_escrow(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.escrow,
+                    &ctx.accounts.give_mint,
+                    ctx.accounts.payer.to_account_info(),
+                    trade.to_account_info(),
+                    &[trade_seeds],
+                )?;
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn create_stake(ctx: Context<CreateStake>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::CreateStake { lamports } => {
+                let wallet_authority = ctx.accounts.wallet_authority.to_account_info();
+                let stake = ctx.accounts.stake.to_account_info();
+                let wallet_key = ctx.accounts.wallet.key();
+                let proposal_key = ctx.accounts.proposal.key();
+                let stake_seeds: &[&[u8]] = &[
+                    "stake".as_bytes(),
+                    wallet_key.as_ref(),
+                    proposal_key.as_ref(),
+                    &[ctx.bumps.stake],
+                ];
+                // anyone can send lamports to the stake address, so the account is funded,
+                // allocated and assigned separately instead of failing on create_account
+                invoke_signed(
+                    &system_instruction::transfer(wallet_authority.key, stake.key, lamports),
+                    &[wallet_authority.clone(), stake.clone()],
+                    &[&[
+                        "authority".as_bytes(),
+                        wallet_key.as_ref(),
+                        &[ctx.bumps.wallet_authority],
+                    ]],
+                )?;
+                invoke_signed(
+                    &system_instruction::allocate(stake.key, StakeStateV2::size_of() as u64),
+                    std::slice::from_ref(&stake),
+                    &[stake_seeds],
+                )?;
+                invoke_signed(
+                    &system_instruction::assign(stake.key, &stake::program::ID),
+                    std::slice::from_ref(&stake),
+                    &[stake_seeds],
+                )?;
+                invoke(
+                    &stake::instruction::initialize(
+                        stake.key,
+                        &Authorized {
+                            staker: wallet_authority.key(),
+                            withdrawer: wallet_authority.key(),
+                        },
+                        &Lockup::default(),
+                    ),
+                    &[stake, ctx.accounts.rent.to_account_info()],
+                )?;
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn delegate_stake(ctx: Context<ManageStake>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::DelegateStake {
+                stake,
+                vote_account,
+            } => {
+                require_keys_eq!(
+                    stake,
+                    ctx.accounts.stake.key(),
+                    WalletError::ProposalInstructionMismatch
+                );
+                let vote = ctx
+                    .accounts
+                    .vote_account
+                    .as_ref()
+                    .ok_or(WalletError::InsufficientAccounts)?;
+                require_keys_eq!(
+                    vote_account,
+                    vote.key(),
+                    WalletError::ProposalInstructionMismatch
+                );
+                let stake_config = ctx
+                    .accounts
+                    .stake_config
+                    .as_ref()
+                    .ok_or(WalletError::InsufficientAccounts)?;
+                invoke_signed(
+                    &stake::instruction::delegate_stake(
+                        ctx.accounts.stake.key,
+                        ctx.accounts.wallet_authority.key,
+                        vote.key,
+                    ),
+                    &[
+                        ctx.accounts.stake.to_account_info(),
+                        vote.to_account_info(),
+                        ctx.accounts.clock.to_account_info(),
+                        ctx.accounts.stake_history.to_account_info(),
+                        stake_config.to_account_info(),
+                        ctx.accounts.wallet_authority.to_account_info(),
+                    ],
+                    &[&[
+                        "authority".as_bytes(),
+                        ctx.accounts.wallet.key().as_ref(),
+                        &[ctx.bumps.wallet_authority],
+                    ]],
+                )?;
             }
             _ => return err!(WalletError::ProposalInstructionMismatch),
         }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn deactivate_stake(ctx: Context<ManageStake>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::DeactivateStake { stake } => {
+                require_keys_eq!(
+                    stake,
+                    ctx.accounts.stake.key(),
+                    WalletError::ProposalInstructionMismatch
+                );
+                invoke_signed(
+                    &stake::instruction::deactivate_stake(
+                        ctx.accounts.stake.key,
+                        ctx.accounts.wallet_authority.key,
+                    ),
+                    &[
+                        ctx.accounts.stake.to_account_info(),
+                        ctx.accounts.clock.to_account_info(),
+                        ctx.accounts.wallet_authority.to_account_info(),
+                    ],
+                    &[&[
+                        "authority".as_bytes(),
+                        ctx.accounts.wallet.key().as_ref(),
+                        &[ctx.bumps.wallet_authority],
+                    ]],
+                )?;
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    // withdrawn lamports go back to the wallet authority
+    pub fn withdraw_stake(ctx: Context<ManageStake>) -> Result<()> {
+        let wallet = ctx.accounts.wallet.load_current()?;
+        let vote_count = ctx.accounts.vote_count.load_current()?;
+        check_executable(&ctx.accounts.proposal, &wallet, &vote_count)?;
+        match ctx.accounts.proposal.proposal {
+            ProposalType::WithdrawStake { stake, lamports } => {
+                require_keys_eq!(
+                    stake,
+                    ctx.accounts.stake.key(),
+                    WalletError::ProposalInstructionMismatch
+                );
+                invoke_signed(
+                    &stake::instruction::withdraw(
+                        ctx.accounts.stake.key,
+                        ctx.accounts.wallet_authority.key,
+                        ctx.accounts.wallet_authority.key,
+                        lamports,
+                        None,
+                    ),
+                    &[
+                        ctx.accounts.stake.to_account_info(),
+                        ctx.accounts.wallet_authority.to_account_info(),
+                        ctx.accounts.clock.to_account_info(),
+                        ctx.accounts.stake_history.to_account_info(),
+                    ],
+                    &[&[
+                        "authority".as_bytes(),
+                        ctx.accounts.wallet.key().as_ref(),
+                        &[ctx.bumps.wallet_authority],
+                    ]],
+                )?;
+            }
+            _ => return err!(WalletError::ProposalInstructionMismatch),
+        }
+        finalize_proposal(
+            &mut ctx.accounts.proposal,
+            &ctx.accounts.vote_count,
+            ctx.accounts.proposer.to_account_info(),
+            ProposalStatus::Executed,
+        )
+    }
+    pub fn migrate_wallet<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateWallet<'info>>,
+    ) -> Result<()> {
+        let payer = ctx.accounts.payer.to_account_info();
+        let wallet = ctx.accounts.wallet.to_account_info();
+        migrate_wallet_config(&wallet, &payer)?;
+        // owner accounts of the wallet are passed in remaining accounts, other accounts of the
+        // old layout can not be migrated
+        for account in ctx.remaining_accounts {
+            migrate_wallet_auth(account, wallet.key, &payer)?;
+        }
         Ok(())
     }
+
+    pub fn close_legacy_proposal(ctx: Context<CloseLegacyProposal>) -> Result<()> {
+        let proposal = ctx.accounts.proposal.to_account_info();
+        let vote_count = ctx.accounts.vote_count.to_account_info();
+        require_eq!(
+            proposal.data_len(),
+            LegacyProposal::LEN,
+            WalletError::InvalidMigrationAccount
+        );
+        require_eq!(
+            vote_count.data_len(),
+            LegacyProposal::VOTE_COUNT_LEN,
+            WalletError::InvalidMigrationAccount
+        );
+        let legacy = LegacyProposal::deserialize(&mut &proposal.try_borrow_data()?[..])?;
+        require!(
+            legacy.discriminator == Proposal::DISCRIMINATOR
+                && vote_count.try_borrow_data()?[..8] == VoteCount::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        require_keys_eq!(
+            legacy.wallet,
+            ctx.accounts.wallet.key(),
+            WalletError::InvalidMigrationAccount
+        );
+        require_keys_eq!(
+            legacy.proposer,
+            ctx.accounts.proposer.key(),
+            WalletError::InvalidMigrationAccount
+        );
+        let proposer = ctx.accounts.proposer.to_account_info();
+        close_account(&proposal, &proposer)?;
+        close_account(&vote_count, &proposer)
+    }
+}
+
+// records the final status of a proposal, closing it unless it is kept as a record, records
+// keep the final tally of the closing vote count and are shrunk to their contents
+fn finalize_proposal<'info>(
+    proposal: &mut Account<'info, Proposal>,
+    vote_count: &AccountLoader<'info, VoteCount>,
+    proposer: AccountInfo<'info>,
+    status: ProposalStatus,
+) -> Result<()> {
+    proposal.set_status(status, Clock::get()?.unix_timestamp);
+    if !proposal.keep_record {
+        return proposal.close(proposer);
+    }
+    let vote_count = vote_count.load_current()?;
+    proposal.votes = vote_count.votes;
+    proposal.weight = vote_count.weight;
+    resize_account(
+        &proposal.to_account_info(),
+        proposal.record_space(),
+        &proposer,
+    )
+}
+
+// checks shared by the execute instructions, expired and stale proposals can not be executed
+// and have to be finalised with the finalize instruction
+fn check_executable(
+    proposal: &Proposal,
+    wallet: &WalletConfig,
+    vote_count: &VoteCount,
+) -> Result<()> {
+    require!(proposal.is_open(), WalletError::ProposalNotOpen);
+    match final_status(proposal, wallet, vote_count)? {
+        Some(ProposalStatus::Expired) => return err!(WalletError::ProposalExpired),
+        Some(_) => return err!(WalletError::StaleProposal),
+        None => {}
+    }
+    // unpausing is the only proposal that can be executed while the wallet is paused
+    if !matches!(proposal.proposal, ProposalType::Unpause) {
+        require!(!wallet.is_paused(), WalletError::WalletPaused);
+    }
+    require_gte!(
+        vote_count.weight,
+        vote_count.threshold,
+        WalletError::NotEnoughVotes
+    );
+    Ok(())
+}
+
+// status an open proposal ends with if it expired or was created under an older wallet config
+fn final_status(
+    proposal: &Proposal,
+    wallet: &WalletConfig,
+    vote_count: &VoteCount,
+) -> Result<Option<ProposalStatus>> {
+    // a batch that started executing runs to the end, its transfers were approved together
+    if proposal.executed_transfers > 0 {
+        return Ok(None);
+    }
+    if Clock::get()?.unix_timestamp >= vote_count.proposed_time + wallet.proposal_lifetime {
+        return Ok(Some(ProposalStatus::Expired));
+    }
+    // proposals created under an older wallet config are rejected
+    if proposal.config_version != wallet.config_version {
+        return Ok(Some(ProposalStatus::Rejected));
+    }
+    Ok(None)
+}
+
+// checks the entries of a batch transfer, their mint and receive accounts are passed as
+// (mint, receive account) pairs in order
+fn check_transfer_entries<'info>(
+    transfers: &[TransferEntry],
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    require_eq!(
+        accounts.len(),
+        transfers.len() * 2,
+        WalletError::InsufficientAccounts
+    );
+    for (transfer, accounts) in transfers.iter().zip(accounts.chunks(2)) {
+        require!(transfer.amount > 0, WalletError::ZeroSendAmount);
+        let mint = InterfaceAccount::<Mint>::try_from(&accounts[0])?;
+        let token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+        require_keys_eq!(
+            transfer.receive_account,
+            token_account.key(),
+            WalletError::TokenAccountMismatch
+        );
+        require_keys_eq!(transfer.token_mint, mint.key(), WalletError::MintMismatch);
+        require_keys_eq!(
+            transfer.token_mint,
+            token_account.mint,
+            WalletError::MintMismatch
+        );
+        require_eq!(
+            transfer.decimals,
+            mint.decimals,
+            WalletError::DecimalsMismatch
+        );
+        check_token_extensions(&mint, &token_account, transfer.amount)?;
+    }
+    Ok(())
+}
+
+// rejects token-2022 mints and accounts whose extensions would make the transfer fail
+fn check_token_extensions(
+    mint: &InterfaceAccount<Mint>,
+    token_account: &InterfaceAccount<TokenAccount>,
+    amount: u64,
+) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+    require!(
+        mint_state.get_extension::<NonTransferable>().is_err(),
+        WalletError::NonTransferableMint
+    );
+    if let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() {
+        let fee = fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .unwrap();
+        require_gt!(amount, fee, WalletError::TransferFeeExceedsAmount);
+        msg!("Transfer fee of {} will be withheld from the amount", fee);
+    }
+    if let Some(hook_program) = transfer_hook::get_program_id(&mint_state) {
+        require_keys_neq!(hook_program, ID, WalletError::InvalidTransferHook);
+    }
+    let account_info = token_account.to_account_info();
+    let account_data = account_info.try_borrow_data()?;
+    let account_state =
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data)?;
+    if let Ok(memo_transfer) = account_state.get_extension::<MemoTransfer>() {
+        require!(
+            !bool::from(memo_transfer.require_incoming_transfer_memos),
+            WalletError::MemoRequired
+        );
+    }
+    Ok(())
+}
+
+// closes an emptied escrow token account, fees withheld in it block closing and are
+// harvested to the mint first
+fn close_escrow<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    escrow: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let escrow_info = escrow.to_account_info();
+    let withheld = {
+        let escrow_data = escrow_info.try_borrow_data()?;
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&escrow_data)?
+            .get_extension::<TransferFeeAmount>()
+            .map_or(0, |fee_amount| u64::from(fee_amount.withheld_amount))
+    };
+    if withheld > 0 {
+        invoke(
+            &spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                token_program.key,
+                &mint.key(),
+                &[&escrow.key()],
+            )?,
+            &[mint.to_account_info(), escrow_info.clone()],
+        )?;
+    }
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: escrow_info,
+            destination,
+            authority,
+        },
+        signer_seeds,
+    ))
+}
+
+// closes a vesting once nothing is left to claim, together with its escrow
+fn close_vesting<'info>(
+    vesting: &Account<'info, Vesting>,
+    escrow: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    payer: AccountInfo<'info>,
+) -> Result<()> {
+    close_escrow(
+        token_program,
+        escrow,
+        mint,
+        payer.clone(),
+        vesting.to_account_info(),
+        &[&[
+            "vesting".as_bytes(),
+            vesting.wallet.as_ref(),
+            vesting.proposal.as_ref(),
+            &[vesting.bump],
+        ]],
+    )?;
+    vesting.close(payer)
+}
+
+// vote weight needed to execute a proposal, the m of the proposal type is used unless
+// transfers reach a tier by the total amount sent per mint, transfers to addresses missing
+// from the address book are rejected or need a higher threshold depending on the wallet setting
+fn proposal_threshold(
+    wallet: &WalletConfig,
+    proposal: &ProposalType,
+    address_book: Option<&AddressBook>,
+    transfer_tiers: Option<&TransferTiers>,
+) -> Result<u64> {
+    let transfer_totals = proposal.transfer_totals();
+    // a proposer could otherwise leave out the tiers to fall back to the type threshold
+    require!(
+        transfer_tiers.is_some() || !wallet.has_transfer_tiers() || transfer_totals.is_empty(),
+        WalletError::TransferTiersRequired
+    );
+    // mints without tiers need the m of the proposal type, the highest m of all mints is used
+    let type_m = wallet.type_m(proposal.index());
+    let mut threshold = wallet.threshold_for(
+        transfer_totals
+            .iter()
+            .map(|(mint, amount)| {
+                transfer_tiers
+                    .and_then(|tiers| tiers.required_m(mint, *amount))
+                    .unwrap_or(type_m)
+            })
+            .max()
+            .unwrap_or(type_m),
+    );
+    // overrides can only be changed with the highest threshold of the wallet, taking the
+    // new m into account so an override can not be raised by a lower one
+    if let ProposalType::SetTypeThreshold { m, .. } = proposal {
+        threshold = wallet.threshold_for(wallet.max_m().max(*m));
+    }
+    let unlisted = proposal
+        .recipients()
+        .iter()
+        .any(|recipient| !address_book.is_some_and(|book| book.contains(recipient)));
+    if unlisted && wallet.address_book_mode != AddressBookMode::Off as u8 {
+        require_neq!(
+            wallet.address_book_mode,
+            AddressBookMode::Enforce as u8,
+            WalletError::RecipientNotInAddressBook
+        );
+        threshold = threshold.max(wallet.unlisted_threshold());
+    }
+    Ok(threshold)
+}
+
+// closes a program account that is not deserialized by the accounts struct
+fn close_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    **destination.try_borrow_mut_lamports()? += account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&System::id());
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+// tops up the rent of an account that is about to grow
+fn pay_rent<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+) -> Result<()> {
+    let required = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if required > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, required),
+            &[payer.clone(), account.clone()],
+        )?;
+    }
+    Ok(())
+}
+
+// resizes an account, topping up its rent or refunding the excess to the payer
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+) -> Result<()> {
+    pay_rent(account, new_len, payer)?;
+    let excess = account
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(new_len));
+    if excess > 0 {
+        **account.try_borrow_mut_lamports()? -= excess;
+        **payer.try_borrow_mut_lamports()? += excess;
+    }
+    account.realloc(new_len, false)?;
+    Ok(())
+}
+
+// rewrites a wallet config of the legacy layout in the zero copy layout, the owner ids are
+// kept and every owner gets a weight of one
+fn migrate_wallet_config<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+) -> Result<()> {
+    let data_len = account.data_len();
+    if data_len >= WalletConfig::len()
+        && account.try_borrow_data()?[8 + std::mem::offset_of!(WalletConfig, version)]
+            == ACCOUNT_VERSION
+    {
+        return Ok(());
+    }
+    require_eq!(
+        data_len,
+        LegacyWalletConfig::LEN,
+        WalletError::InvalidMigrationAccount
+    );
+    let legacy = LegacyWalletConfig::deserialize(&mut &account.try_borrow_data()?[..])?;
+    require_gte!(
+        WalletConfig::MAX_NAME_LEN,
+        legacy.name.len(),
+        WalletError::InvalidMigrationAccount
+    );
+    let new_len = WalletConfig::space(legacy.owner_identities.len() * 8);
+    pay_rent(account, new_len, payer)?;
+    account.realloc(new_len, false)?;
+    let mut data = account.try_borrow_mut_data()?;
+    data[8..].fill(0);
+    let (header, owner_identities) = data.split_at_mut(WalletConfig::len());
+    let wallet = bytemuck::from_bytes_mut::<WalletConfig>(&mut header[8..]);
+    owner_identities.copy_from_slice(&legacy.owner_identities);
+    wallet.name[..legacy.name.len()].copy_from_slice(legacy.name.as_bytes());
+    wallet.m = legacy.m;
+    wallet.n = legacy.n;
+    wallet.owners = legacy.owners as u16;
+    wallet.total_weight = legacy.owners as u64;
+    wallet.proposal_lifetime = legacy.proposal_lifetime;
+    wallet.pause_threshold = 1;
+    wallet.version = ACCOUNT_VERSION;
+    Ok(())
+}
+
+// rewrites an owner account of the legacy layout with a weight of one
+fn migrate_wallet_auth<'info>(
+    account: &AccountInfo<'info>,
+    wallet: &Pubkey,
+    payer: &AccountInfo<'info>,
+) -> Result<()> {
+    require_keys_eq!(*account.owner, ID, ErrorCode::AccountOwnedByWrongProgram);
+    if account.data_len() == WalletAuth::len() {
+        let wallet_auth = WalletAuth::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        require!(
+            wallet_auth.is_current() && wallet_auth.wallet == *wallet,
+            WalletError::InvalidMigrationAccount
+        );
+        return Ok(());
+    }
+    require_eq!(
+        account.data_len(),
+        LegacyWalletAuth::LEN,
+        WalletError::InvalidMigrationAccount
+    );
+    let legacy = LegacyWalletAuth::deserialize(&mut &account.try_borrow_data()?[..])?;
+    require!(
+        legacy.discriminator == WalletAuth::DISCRIMINATOR
+            || legacy.discriminator == LegacyWalletAuth::MISTYPED_DISCRIMINATOR,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    require_keys_eq!(legacy.wallet, *wallet, WalletError::InvalidMigrationAccount);
+    pay_rent(account, WalletAuth::len(), payer)?;
+    account.realloc(WalletAuth::len(), false)?;
+    let wallet_auth = WalletAuth {
+        owner: legacy.owner,
+        wallet: legacy.wallet,
+        id: legacy.id as u16,
+        added_time: legacy.added_time,
+        weight: 1,
+        version: ACCOUNT_VERSION,
+        reserved: [0; 32],
+    };
+    wallet_auth.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])
 }
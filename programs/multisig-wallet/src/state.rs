@@ -1,16 +1,49 @@
+use crate::error::WalletError;
 use anchor_lang::prelude::*;
-use borsh::{BorshDeserialize, BorshSerialize};
+use std::cell::{Ref, RefMut};
 
 pub trait Len {
     fn len() -> usize;
 }
 
+// owner id bitmap stored after the zero copy header of an account
+pub type BitmapMut<'a> = RefMut<'a, [u8]>;
+
+// layout version of all program accounts
+pub const ACCOUNT_VERSION: u8 = 1;
+
+pub trait Versioned {
+    fn version(&self) -> u8;
+
+    // accounts of another layout version have to be migrated before they are used
+    fn is_current(&self) -> bool {
+        self.version() == ACCOUNT_VERSION
+    }
+}
+
+// zero copy accounts are loaded only once they have the current layout, accounts of the old
+// layout are shorter than the header and would otherwise fail with a generic error
+pub trait LoadCurrent<T> {
+    fn load_current(&self) -> Result<Ref<'_, T>>;
+    fn load_current_mut(&self) -> Result<RefMut<'_, T>>;
+}
+
+fn check_current(account: &AccountInfo, len: usize, version_offset: usize) -> Result<()> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= len && data[8 + version_offset] == ACCOUNT_VERSION,
+        WalletError::AccountNotMigrated
+    );
+    Ok(())
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub enum ProposalType {
     Transfer {
         token_mint: Pubkey,
         receive_account: Pubkey,
         amount: u64,
+        decimals: u8,
     },
     AddOwner {
         user: Pubkey,
@@ -18,19 +51,173 @@ pub enum ProposalType {
     ChangeProposalLifetime {
         duration: i64,
     },
+    SetGuardians {
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        recovery_delay: i64,
+    },
+    ChangePauseThreshold {
+        threshold: u8,
+    },
+    Unpause,
+    ChangeOwnerWeight {
+        owner: Pubkey,
+        weight: u32,
+    },
+    BatchTransfer {
+        transfers: Vec<TransferEntry>,
+    },
+    // fields left as none are not changed
+    UpdateMetadata {
+        name: Option<String>,
+        description: Option<String>,
+        icon_uri: Option<String>,
+    },
+    // entries with an address already in the book are renamed
+    UpdateAddressBook {
+        add: Vec<AddressBookEntry>,
+        remove: Vec<Pubkey>,
+    },
+    ChangeAddressBookMode {
+        mode: AddressBookMode,
+        unlisted_m: u8, // used with the higher threshold mode
+    },
+    // an empty tier list removes the tiers of the mint
+    SetTransferTiers {
+        mint: Pubkey,
+        tiers: Vec<TransferTier>,
+    },
+    // m of zero removes the override of the proposal type
+    SetTypeThreshold {
+        proposal_type: u8, // index of the ProposalType variant
+        m: u8,
+    },
+    // pays amount every interval seconds, count times, starting from execution
+    RecurringTransfer {
+        mint: Pubkey,
+        receive_account: Pubkey,
+        amount: u64,
+        interval: i64,
+        count: u32,
+    },
+    CancelRecurringTransfer {
+        schedule: Pubkey,
+    },
+    // amount vests linearly from start to end, nothing can be claimed before the cliff
+    CreateVesting {
+        mint: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        start: i64,
+        cliff: i64,
+        end: i64,
+    },
+    ClawbackVesting {
+        vesting: Pubkey,
+    },
+    // give side is escrowed on execution, the counterparty completes the trade
+    OtcTrade {
+        give_mint: Pubkey,
+        give_amount: u64,
+        want_mint: Pubkey,
+        want_amount: u64,
+        counterparty: Pubkey,
+    },
+    CancelOtcTrade {
+        trade: Pubkey,
+    },
+    // stake accounts use the wallet authority as stake and withdraw authority
+    CreateStake {
+        lamports: u64,
+    },
+    DelegateStake {
+        stake: Pubkey,
+        vote_account: Pubkey,
+    },
+    DeactivateStake {
+        stake: Pubkey,
+    },
+    WithdrawStake {
+        stake: Pubkey,
+        lamports: u64,
+    },
+}
+
+// transfers of at least min_amount need m votes out of n, tiers are sorted by min_amount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TransferTier {
+    pub min_amount: u64,
+    pub m: u8,
 }
 
-#[derive(BorshSerialize, BorshDeserialize)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MintTiers {
+    pub mint: Pubkey,
+    pub tiers: Vec<TransferTier>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AddressBookEntry {
+    pub name: String, // max length of 32 bytes
+    pub address: Pubkey,
+}
+
+// how create_proposal treats transfers to addresses missing from the address book
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AddressBookMode {
+    Off,
+    Enforce,
+    HigherThreshold,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TransferEntry {
+    pub token_mint: Pubkey,
+    pub receive_account: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    Draft,
+    Active,
+    Approved,
+    Rejected,
+    Executed,
+    Cancelled,
+    Expired,
+}
+
+// time of each status change, zero if the proposal never reached the status
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ProposalTimestamps {
+    pub drafted: i64,
+    pub activated: i64,
+    pub approved: i64,
+    pub rejected: i64,
+    pub executed: i64,
+    pub cancelled: i64,
+    pub expired: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct RawWalletAuth {
     pub discriminator: [u8; 8],
     pub owner: Pubkey,
     pub wallet: Pubkey,
-    pub id: u8,
+    pub id: u16,
     pub added_time: i64,
+    pub weight: u32,
+    pub version: u8,
+    pub reserved: [u8; 32],
 }
 
-#[account]
-pub struct WalletConfig {
+// borsh layouts of the wallet config and owner accounts before the zero copy wallet config,
+// only read to migrate wallets created with them
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyWalletConfig {
+    pub discriminator: [u8; 8],
     pub name: String, // max length of 20 bytes
     pub m: u8,
     pub n: u8,
@@ -39,26 +226,197 @@ pub struct WalletConfig {
     pub proposal_lifetime: i64,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyWalletAuth {
+    pub discriminator: [u8; 8],
+    pub owner: Pubkey,
+    pub wallet: Pubkey,
+    pub id: u8,
+    pub added_time: i64,
+}
+
+// proposals of the old layout can not be migrated, only the fields needed to close them
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyProposal {
+    pub discriminator: [u8; 8],
+    pub wallet: Pubkey,
+    pub proposer: Pubkey,
+}
+
+// fields are ordered by alignment so the zero copy layout has no implicit padding
+// the account data continues with the owner id bitmap, which grows as owners are added
+#[account(zero_copy)]
+pub struct WalletConfig {
+    pub total_weight: u64, // sum of the weights of all owners
+    pub proposal_lifetime: i64,
+    pub proposal_count: u64, // index of the next proposal
+    pub config_version: u32, // bumped whenever an executed proposal changes the config
+    pub owners: u16,
+    pub name: [u8; 20], // zero padded, max length of 20 bytes
+    pub m: u8,
+    pub n: u8,
+    pub paused: u8,          // 1 if the wallet is paused
+    pub pause_threshold: u8, // number of owners needed to pause the wallet
+    pub version: u8,
+    pub padding: [u8; 1],
+    pub create_key: Pubkey, // wallet address is derived from it, default for keypair wallets
+    pub bump: u8,
+    pub address_book_mode: u8, // AddressBookMode
+    // m used for transfers to addresses missing from the address book
+    pub unlisted_m: u8,
+    // m used for each ProposalType variant instead of the wallet m, zero if not overridden
+    pub type_m: [u8; 24],
+    pub transfer_tiers: u8, // 1 if any mint has transfer tiers
+    pub padding_2: [u8; 4],
+    pub reserved: [u8; 64],
+}
+
 #[account]
 pub struct WalletAuth {
     pub owner: Pubkey,
     pub wallet: Pubkey,
-    pub id: u8,
+    pub id: u16,
     pub added_time: i64,
+    pub weight: u32,
+    pub version: u8,
+    pub reserved: [u8; 32],
 }
 
 #[account]
 pub struct Proposal {
     pub wallet: Pubkey,
+    pub index: u64,
     pub proposer: Pubkey,
+    pub config_version: u32,
+    pub status: ProposalStatus,
+    pub timestamps: ProposalTimestamps,
+    pub keep_record: bool, // keep the proposal as a record once it is finalised
+    // final tally of a proposal kept as a record, zero until it is finalised
+    pub votes: u16,
+    pub weight: u64,
+    pub executed_transfers: u32, // transfers of a batch executed so far
     pub proposal: ProposalType,
+    pub version: u8,
+    pub reserved: [u8; 32],
 }
 
-#[account]
+// the account data continues with the owner snapshot and the vote record bitmaps,
+// both as long as the owner bitmap of the wallet
+#[account(zero_copy)]
 pub struct VoteCount {
     pub proposed_time: i64,
-    pub votes: u8,
-    pub vote_record: [u8; 32],
+    pub weight: u64, // total weight of the owners who voted
+    // threshold of the wallet when the proposal was created
+    pub threshold: u64,
+    pub votes: u16,
+    pub version: u8,
+    pub padding: [u8; 5],
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct AddressBook {
+    pub wallet: Pubkey,
+    pub entries: Vec<AddressBookEntry>,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct TransferTiers {
+    pub wallet: Pubkey,
+    pub mints: Vec<MintTiers>,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct PaymentSchedule {
+    pub wallet: Pubkey,
+    pub payer: Pubkey, // receives the rent once the schedule is closed
+    pub mint: Pubkey,
+    pub receive_account: Pubkey,
+    pub amount: u64,
+    pub interval: i64,
+    pub next_payment: i64, // time from which the next instalment can be paid
+    pub remaining: u32,    // instalments left to pay
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct Vesting {
+    pub wallet: Pubkey,
+    pub proposal: Pubkey, // proposal which created the vesting, part of the seeds
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub escrow: Pubkey, // token account holding the unclaimed amount
+    pub payer: Pubkey,  // receives the rent once everything is claimed
+    pub amount: u64,    // total amount vested by the end
+    pub claimed: u64,
+    pub start: i64,
+    pub cliff: i64,
+    pub end: i64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct OtcTrade {
+    pub wallet: Pubkey,
+    pub proposal: Pubkey, // proposal which created the trade, part of the seeds
+    pub payer: Pubkey,    // receives the rent once the trade is closed
+    pub counterparty: Pubkey,
+    pub give_mint: Pubkey,
+    pub want_mint: Pubkey,
+    pub escrow: Pubkey, // token account holding the give side
+    pub want_amount: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct WalletMetadata {
+    pub wallet: Pubkey,
+    pub description: String, // max length of 256 bytes
+    pub icon_uri: String,    // max length of 200 bytes
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct Delegation {
+    pub wallet: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub expiry: i64,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct GuardianSet {
+    pub wallet: Pubkey,
+    pub guardians: Vec<Pubkey>, // max of 10 guardians
+    pub threshold: u8,
+    pub recovery_delay: i64,
+    pub set_time: i64,
+    pub version: u8,
+    pub reserved: [u8; 32],
+}
+
+#[account]
+pub struct Recovery {
+    pub wallet: Pubkey,
+    pub owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub initiator: Pubkey,
+    pub initiated_time: i64,
+    pub approvals: u16, // bit i is set if guardian at index i approved
+    pub version: u8,
+    pub reserved: [u8; 32],
 }
 
 macro_rules! generate_implementations {
@@ -73,14 +431,430 @@ macro_rules! generate_implementations {
     }
 }
 
-generate_implementations!(WalletAuth, Proposal, VoteCount);
+generate_implementations!(
+    WalletConfig,
+    WalletAuth,
+    VoteCount,
+    Delegation,
+    Recovery,
+    PaymentSchedule,
+    Vesting,
+    OtcTrade
+);
+
+macro_rules! generate_versioned {
+    ($($account:ident),+ $(,)?) => {
+        $(
+            impl Versioned for $account {
+                fn version(&self) -> u8 {
+                    self.version
+                }
+            }
+        )+
+    }
+}
+
+macro_rules! generate_load_current {
+    ($($account:ident),+ $(,)?) => {
+        $(
+            impl $account {
+                fn check_current(account: &AccountInfo) -> Result<()> {
+                    check_current(
+                        account,
+                        Self::len(),
+                        std::mem::offset_of!($account, version),
+                    )
+                }
+            }
+
+            impl<'info> LoadCurrent<$account> for AccountLoader<'info, $account> {
+                fn load_current(&self) -> Result<Ref<'_, $account>> {
+                    $account::check_current(self.as_ref())?;
+                    self.load()
+                }
+
+                fn load_current_mut(&self) -> Result<RefMut<'_, $account>> {
+                    $account::check_current(self.as_ref())?;
+                    self.load_mut()
+                }
+            }
+        )+
+    }
+}
+
+generate_load_current!(WalletConfig, VoteCount);
+
+generate_versioned!(
+    WalletAuth,
+    Proposal,
+    WalletMetadata,
+    AddressBook,
+    TransferTiers,
+    PaymentSchedule,
+    Vesting,
+    OtcTrade,
+    Delegation,
+    GuardianSet,
+    Recovery
+);
 
 impl WalletConfig {
     pub const MAX_NAME_LEN: usize = 20;
+    // vote counts hold two bitmaps of the owner ids after a header of at most 72 bytes and
+    // are created through a cpi, which caps them at 10240 bytes: 2 * 5084 bytes fit 40672 ids
+    pub const MAX_OWNERS: u16 = 40672;
+    // one per ProposalType variant, type_m keeps a spare byte for the next variant
+    pub const MAX_PROPOSAL_TYPES: usize = ProposalType::LAST_INDEX as usize + 1;
+
+    // account size with an owner bitmap large enough for the given number of ids
+    pub fn space(ids: usize) -> usize {
+        Self::len() + ids.div_ceil(8)
+    }
+
+    // size needed to add one more owner, the bitmap only grows once every id is taken
+    pub fn space_for_new_owner(wallet: &AccountLoader<WalletConfig>) -> Result<usize> {
+        let owners = wallet.load_current()?.owners as usize;
+        Ok(wallet.as_ref().data_len().max(Self::space(owners + 1)))
+    }
+
+    pub fn bitmap_len(wallet: &AccountLoader<WalletConfig>) -> usize {
+        wallet.as_ref().data_len() - Self::len()
+    }
+
+    pub fn load_with_owners<'a>(
+        wallet: &'a AccountLoader<WalletConfig>,
+    ) -> Result<(Ref<'a, Self>, Ref<'a, [u8]>)> {
+        Self::check_current(wallet.as_ref())?;
+        let data = wallet.as_ref().try_borrow_data()?;
+        let (wallet, owner_identities) = Ref::map_split(data, |data| {
+            let (header, owner_identities) = data.split_at(Self::len());
+            (bytemuck::from_bytes::<Self>(&header[8..]), owner_identities)
+        });
+        Ok((wallet, owner_identities))
+    }
+
+    pub fn load_mut_with_owners<'a>(
+        wallet: &'a AccountLoader<WalletConfig>,
+    ) -> Result<(RefMut<'a, Self>, BitmapMut<'a>)> {
+        Self::check_current(wallet.as_ref())?;
+        let data = wallet.as_ref().try_borrow_mut_data()?;
+        let (wallet, owner_identities) = RefMut::map_split(data, |data| {
+            let (header, owner_identities) = data.split_at_mut(Self::len());
+            (
+                bytemuck::from_bytes_mut::<Self>(&mut header[8..]),
+                owner_identities,
+            )
+        });
+        Ok((wallet, owner_identities))
+    }
+
+    // vote weight needed to execute a proposal, m/n of the total weight
+    pub fn threshold(&self) -> u64 {
+        self.threshold_for(self.m)
+    }
+
+    // vote weight needed for m votes out of n, rounded up so a proposal never passes with
+    // less than m/n of the weight and never without a vote
+    pub fn threshold_for(&self, m: u8) -> u64 {
+        let n = self.n as u64;
+        (self
+            .total_weight
+            .checked_mul(m as u64)
+            .unwrap()
+            .checked_add(n - 1)
+            .unwrap()
+            / n)
+            .max(1)
+    }
+
+    // m of the proposal type, the wallet m unless overridden
+    pub fn type_m(&self, proposal_type: u8) -> u8 {
+        match self.type_m[proposal_type as usize] {
+            0 => self.m,
+            m => m,
+        }
+    }
+
+    // highest m among the wallet m and the overrides
+    pub fn max_m(&self) -> u8 {
+        self.type_m.iter().fold(self.m, |max, m| max.max(*m))
+    }
+
+    // vote weight needed for transfers to addresses missing from the address book
+    pub fn unlisted_threshold(&self) -> u64 {
+        self.threshold_for(self.unlisted_m)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    pub fn has_transfer_tiers(&self) -> bool {
+        self.transfer_tiers != 0
+    }
+}
+
+impl ProposalType {
+    // index of the last variant, new variants are appended and take over this constant
+    pub const LAST_INDEX: u8 = 22;
+
+    // index of the variant, used for the per type thresholds
+    pub fn index(&self) -> u8 {
+        match self {
+            ProposalType::Transfer { .. } => 0,
+            ProposalType::AddOwner { .. } => 1,
+            ProposalType::ChangeProposalLifetime { .. } => 2,
+            ProposalType::SetGuardians { .. } => 3,
+            ProposalType::ChangePauseThreshold { .. } => 4,
+            ProposalType::Unpause => 5,
+            ProposalType::ChangeOwnerWeight { .. } => 6,
+            ProposalType::BatchTransfer { .. } => 7,
+            ProposalType::UpdateMetadata { .. } => 8,
+            ProposalType::UpdateAddressBook { .. } => 9,
+            ProposalType::ChangeAddressBookMode { .. } => 10,
+            ProposalType::SetTransferTiers { .. } => 11,
+            ProposalType::SetTypeThreshold { .. } => 12,
+            ProposalType::RecurringTransfer { .. } => 13,
+            ProposalType::CancelRecurringTransfer { .. } => 14,
+            ProposalType::CreateVesting { .. } => 15,
+            ProposalType::ClawbackVesting { .. } => 16,
+            ProposalType::OtcTrade { .. } => 17,
+            ProposalType::CancelOtcTrade { .. } => 18,
+            ProposalType::CreateStake { .. } => 19,
+            ProposalType::DelegateStake { .. } => 20,
+            ProposalType::DeactivateStake { .. } => 21,
+            ProposalType::WithdrawStake { .. } => Self::LAST_INDEX,
+        }
+    }
+
+    // addresses receiving funds if the proposal is executed
+    pub fn recipients(&self) -> Vec<Pubkey> {
+        match self {
+            ProposalType::Transfer {
+                receive_account, ..
+            } => vec![*receive_account],
+            ProposalType::BatchTransfer { transfers } => transfers
+                .iter()
+                .map(|transfer| transfer.receive_account)
+                .collect(),
+            ProposalType::RecurringTransfer {
+                receive_account, ..
+            } => vec![*receive_account],
+            ProposalType::CreateVesting { beneficiary, .. } => vec![*beneficiary],
+            ProposalType::OtcTrade { counterparty, .. } => vec![*counterparty],
+            _ => Vec::new(),
+        }
+    }
+
+    // total amount sent per mint if the proposal is executed
+    pub fn transfer_totals(&self) -> Vec<(Pubkey, u64)> {
+        let mut totals: Vec<(Pubkey, u64)> = Vec::new();
+        let transfers: Vec<(Pubkey, u64)> = match self {
+            ProposalType::Transfer {
+                token_mint, amount, ..
+            } => vec![(*token_mint, *amount)],
+            ProposalType::BatchTransfer { transfers } => transfers
+                .iter()
+                .map(|transfer| (transfer.token_mint, transfer.amount))
+                .collect(),
+            // all instalments are approved at once
+            ProposalType::RecurringTransfer {
+                mint,
+                amount,
+                count,
+                ..
+            } => vec![(*mint, amount.saturating_mul(*count as u64))],
+            ProposalType::CreateVesting { mint, amount, .. } => vec![(*mint, *amount)],
+            ProposalType::OtcTrade {
+                give_mint,
+                give_amount,
+                ..
+            } => vec![(*give_mint, *give_amount)],
+            _ => Vec::new(),
+        };
+        for (mint, amount) in transfers {
+            match totals
+                .iter_mut()
+                .find(|(total_mint, _)| *total_mint == mint)
+            {
+                Some((_, total)) => *total = total.saturating_add(amount),
+                None => totals.push((mint, amount)),
+            }
+        }
+        totals
+    }
+}
+
+impl Proposal {
+    const TRANSFER_LEN: usize = 1 + 32 + 32 + 8 + 1;
+
+    // init reads the zeroed account as a transfer proposal before it is set, so the account
+    // is never smaller than one
+    pub fn space(proposal: &ProposalType) -> usize {
+        let proposal_len = proposal.try_to_vec().unwrap().len().max(Self::TRANSFER_LEN);
+        8 + 32 + 8 + 32 + 4 + 1 + 7 * 8 + 1 + 2 + 8 + 4 + proposal_len + 1 + 32
+    }
+
+    // size of a finalised proposal kept as a record, without the room left for a transfer
+    pub fn record_space(&self) -> usize {
+        8 + self.try_to_vec().unwrap().len()
+    }
+
+    pub fn set_status(&mut self, status: ProposalStatus, time: i64) {
+        self.status = status;
+        let timestamps = &mut self.timestamps;
+        match status {
+            ProposalStatus::Draft => timestamps.drafted = time,
+            ProposalStatus::Active => timestamps.activated = time,
+            ProposalStatus::Approved => timestamps.approved = time,
+            ProposalStatus::Rejected => timestamps.rejected = time,
+            ProposalStatus::Executed => timestamps.executed = time,
+            ProposalStatus::Cancelled => timestamps.cancelled = time,
+            ProposalStatus::Expired => timestamps.expired = time,
+        }
+    }
+
+    // proposals can be voted on and executed only while open
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            ProposalStatus::Active | ProposalStatus::Approved
+        )
+    }
+}
+
+impl VoteCount {
+    pub fn space(bitmap_len: usize) -> usize {
+        Self::len() + 2 * bitmap_len
+    }
+
+    // header, owner snapshot and vote record
+    pub fn load_mut_with_bitmaps<'a>(
+        vote_count: &'a AccountLoader<VoteCount>,
+    ) -> Result<(RefMut<'a, Self>, BitmapMut<'a>, BitmapMut<'a>)> {
+        Self::check_current(vote_count.as_ref())?;
+        let data = vote_count.as_ref().try_borrow_mut_data()?;
+        let (header, bitmaps) = RefMut::map_split(data, |data| {
+            let (header, bitmaps) = data.split_at_mut(Self::len());
+            (bytemuck::from_bytes_mut::<Self>(&mut header[8..]), bitmaps)
+        });
+        let (owner_snapshot, vote_record) = RefMut::map_split(bitmaps, |bitmaps| {
+            let bitmap_len = bitmaps.len() / 2;
+            bitmaps.split_at_mut(bitmap_len)
+        });
+        Ok((header, owner_snapshot, vote_record))
+    }
+}
+
+impl LegacyWalletConfig {
+    // space the accounts were created with
+    pub const LEN: usize = 8 + (4 + WalletConfig::MAX_NAME_LEN) + 1 + 1 + 1 + 32 + 8;
+}
+
+impl LegacyWalletAuth {
+    // space the accounts were created with, the size of the struct including padding
+    pub const LEN: usize = 8 + 80;
+    // owners other than the creator were written with two bytes of the discriminator swapped
+    pub const MISTYPED_DISCRIMINATOR: [u8; 8] = [131, 111, 99, 219, 98, 234, 255, 179];
+}
+
+impl LegacyProposal {
+    // space the accounts were created with, the proposal type took 80 bytes
+    pub const LEN: usize = 8 + 32 + 32 + 80;
+    // space the vote counts of the old layout were created with
+    pub const VOTE_COUNT_LEN: usize = 8 + 48;
+}
+
+impl TransferEntry {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+impl AddressBook {
+    pub const MAX_ENTRIES: usize = 100;
+    pub const MAX_NAME_LEN: usize = 32;
+
+    pub fn space(entries: &[AddressBookEntry]) -> usize {
+        8 + 32
+            + 4
+            + entries
+                .iter()
+                .map(|entry| 4 + entry.name.len() + 32)
+                .sum::<usize>()
+            + 1
+            + 32
+    }
+
+    pub fn contains(&self, address: &Pubkey) -> bool {
+        self.entries.iter().any(|entry| entry.address == *address)
+    }
+}
+
+impl TransferTiers {
+    pub const MAX_MINTS: usize = 20;
+    pub const MAX_TIERS: usize = 10;
+
+    pub fn space(mints: &[MintTiers]) -> usize {
+        8 + 32
+            + 4
+            + mints
+                .iter()
+                .map(|mint_tiers| 32 + 4 + mint_tiers.tiers.len() * (8 + 1))
+                .sum::<usize>()
+            + 1
+            + 32
+    }
+
+    // m of the highest tier reached by the amount, if the mint has tiers
+    pub fn required_m(&self, mint: &Pubkey, amount: u64) -> Option<u8> {
+        self.mints
+            .iter()
+            .find(|mint_tiers| mint_tiers.mint == *mint)?
+            .tiers
+            .iter()
+            .rev()
+            .find(|tier| amount >= tier.min_amount)
+            .map(|tier| tier.m)
+    }
+}
+
+impl PaymentSchedule {
+    pub const MIN_INTERVAL: i64 = 60 * 60;
+}
+
+impl Vesting {
+    // amount vested at the given time
+    pub fn vested(&self, time: i64) -> u64 {
+        if time < self.cliff {
+            0
+        } else if time >= self.end {
+            self.amount
+        } else {
+            (self.amount as u128 * (time - self.start) as u128 / (self.end - self.start) as u128)
+                as u64
+        }
+    }
+}
+
+impl WalletMetadata {
+    pub const MAX_DESCRIPTION_LEN: usize = 256;
+    pub const MAX_ICON_URI_LEN: usize = 200;
+
+    pub fn space(description: &str, icon_uri: &str) -> usize {
+        8 + 32 + (4 + description.len()) + (4 + icon_uri.len()) + 1 + 32
+    }
+}
+
+impl GuardianSet {
+    pub const MAX_GUARDIANS: usize = 10;
+    pub const MIN_RECOVERY_DELAY: i64 = 3 * 24 * 60 * 60;
+
+    pub fn position(&self, guardian: &Pubkey) -> Option<usize> {
+        self.guardians.iter().position(|key| key == guardian)
+    }
 }
 
-impl Len for WalletConfig {
+impl Len for GuardianSet {
     fn len() -> usize {
-        8 + (4 + Self::MAX_NAME_LEN) + 1 + 1 + 1 + 32 + 8
+        8 + 32 + (4 + 32 * Self::MAX_GUARDIANS) + 1 + 8 + 8 + 1 + 32
     }
 }
@@ -0,0 +1,117 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{address_book_address, assert_wallet_error, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{AddressBookEntry, AddressBookMode, ProposalType};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+struct AddressBook {
+    test_wallet: TestWallet,
+    mint: Pubkey,
+    listed: Pubkey,
+    unlisted: Pubkey,
+}
+
+// a 1 of 2 wallet with one of two token accounts in its address book
+async fn wallet_with_address_book(mode: AddressBookMode, unlisted_m: u8) -> AddressBook {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let mint = test_wallet.create_mint(0).await;
+    let listed = test_wallet
+        .create_token_account(&mint, &Pubkey::new_unique())
+        .await;
+    let unlisted = test_wallet
+        .create_token_account(&mint, &Pubkey::new_unique())
+        .await;
+
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::UpdateAddressBook {
+                add: vec![AddressBookEntry {
+                    name: "vendor".to_string(),
+                    address: listed,
+                }],
+                remove: vec![],
+            },
+        )
+        .await
+        .unwrap();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::UpdateAddressBook {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            payer: payer.pubkey(),
+            address_book: address_book_address(&wallet),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::UpdateAddressBook {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+
+    let proposal = test_wallet
+        .propose(0, ProposalType::ChangeAddressBookMode { mode, unlisted_m })
+        .await
+        .unwrap();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ChangeWalletState {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ChangeAddressBookMode {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    AddressBook {
+        test_wallet,
+        mint,
+        listed,
+        unlisted,
+    }
+}
+
+#[tokio::test]
+async fn enforced_address_book() {
+    let mut book = wallet_with_address_book(AddressBookMode::Enforce, 0).await;
+    let (mint, listed, unlisted) = (book.mint, book.listed, book.unlisted);
+    book.test_wallet
+        .propose_transfer(0, &mint, &listed, 100, 0)
+        .await
+        .unwrap();
+
+    let error = book
+        .test_wallet
+        .propose_transfer(0, &mint, &unlisted, 100, 0)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::RecipientNotInAddressBook);
+}
+
+#[tokio::test]
+async fn higher_threshold_for_unlisted_recipients() {
+    let mut book = wallet_with_address_book(AddressBookMode::HigherThreshold, 2).await;
+    let (mint, listed, unlisted) = (book.mint, book.listed, book.unlisted);
+    let proposal = book
+        .test_wallet
+        .propose_transfer(0, &mint, &listed, 100, 0)
+        .await
+        .unwrap();
+    assert_eq!(book.test_wallet.vote_count(&proposal).await.threshold, 1);
+
+    let proposal = book
+        .test_wallet
+        .propose_transfer(0, &mint, &unlisted, 100, 0)
+        .await
+        .unwrap();
+    assert_eq!(book.test_wallet.vote_count(&proposal).await.threshold, 2);
+}
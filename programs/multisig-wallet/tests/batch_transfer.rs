@@ -0,0 +1,156 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_wallet_error, create_proposal_ix, spl_token, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{ProposalType, TransferEntry};
+use solana_program_test::BanksClientError;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+struct Batch {
+    test_wallet: TestWallet,
+    mint: Pubkey,
+    // wallet account
+    send_account: Pubkey,
+    receive_accounts: Vec<Pubkey>,
+}
+
+// a 1 of 2 wallet holding 1000 tokens of a mint without decimals and three recipients
+async fn funded_wallet() -> Batch {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let authority = test_wallet.authority();
+    let mint = test_wallet.create_mint(0).await;
+    let send_account = test_wallet.create_token_account(&mint, &authority).await;
+    test_wallet.mint_to(&mint, &send_account, 1000).await;
+    let mut receive_accounts = Vec::new();
+    for _ in 0..3 {
+        let owner = Pubkey::new_unique();
+        receive_accounts.push(test_wallet.create_token_account(&mint, &owner).await);
+    }
+    Batch {
+        test_wallet,
+        mint,
+        send_account,
+        receive_accounts,
+    }
+}
+
+impl Batch {
+    // one transfer of amount to each recipient
+    fn transfers(&self, amount: u64, decimals: u8) -> Vec<TransferEntry> {
+        self.receive_accounts
+            .iter()
+            .map(|receive_account| TransferEntry {
+                token_mint: self.mint,
+                receive_account: *receive_account,
+                amount,
+                decimals,
+            })
+            .collect()
+    }
+
+    async fn propose(&mut self, transfers: Vec<TransferEntry>) -> Result<Pubkey, BanksClientError> {
+        let payer = self.test_wallet.payer();
+        let wallet = self.test_wallet.wallet;
+        let index = self.test_wallet.proposal_count;
+        let mut ix = create_proposal_ix(
+            &wallet,
+            &payer.pubkey(),
+            index,
+            ProposalType::BatchTransfer {
+                transfers: transfers.clone(),
+            },
+        );
+        // the mint and receive account of each entry are checked on creation
+        for transfer in &transfers {
+            ix.accounts
+                .push(AccountMeta::new_readonly(transfer.token_mint, false));
+            ix.accounts
+                .push(AccountMeta::new_readonly(transfer.receive_account, false));
+        }
+        self.test_wallet.process(&[ix], &[&payer]).await?;
+        Ok(self.test_wallet.next_proposal())
+    }
+
+    // executes the next transfers of the batch, none of them has a transfer hook
+    fn execute_ix(&self, proposal: &Pubkey, recipients: &[Pubkey]) -> Instruction {
+        let wallet = self.test_wallet.wallet;
+        let mut accounts = multisig_wallet::accounts::BatchTransferFunds {
+            wallet,
+            proposal: *proposal,
+            vote_count: vote_count_address(&wallet, proposal),
+            proposer: self.test_wallet.payer().pubkey(),
+            wallet_authority: self.test_wallet.authority(),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None);
+        for receive_account in recipients {
+            accounts.push(AccountMeta::new(self.send_account, false));
+            accounts.push(AccountMeta::new_readonly(self.mint, false));
+            accounts.push(AccountMeta::new(*receive_account, false));
+        }
+        Instruction {
+            program_id: multisig_wallet::ID,
+            accounts,
+            data: multisig_wallet::instruction::BatchTransfer {
+                hook_accounts: vec![0; recipients.len()],
+            }
+            .data(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn executes_in_chunks() {
+    let mut batch = funded_wallet().await;
+    let payer = batch.test_wallet.payer();
+    let proposal = batch.propose(batch.transfers(100, 0)).await.unwrap();
+
+    let ix = batch.execute_ix(&proposal, &batch.receive_accounts[..2]);
+    batch.test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    let executed = batch.test_wallet.proposal(&proposal).await.unwrap();
+    assert_eq!(executed.executed_transfers, 2);
+    assert_eq!(
+        batch.test_wallet.token_balance(&batch.send_account).await,
+        800
+    );
+
+    // the rest of a started batch still runs once the proposal has expired
+    batch.test_wallet.advance_clock(2 * 24 * 60 * 60).await;
+    let ix = batch.execute_ix(&proposal, &batch.receive_accounts[2..]);
+    batch.test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    assert!(batch.test_wallet.proposal(&proposal).await.is_none());
+    let vote_count = vote_count_address(&batch.test_wallet.wallet, &proposal);
+    assert!(batch.test_wallet.account(&vote_count).await.is_none());
+    assert_eq!(
+        batch.test_wallet.token_balance(&batch.send_account).await,
+        700
+    );
+    for receive_account in batch.receive_accounts.clone() {
+        assert_eq!(batch.test_wallet.token_balance(&receive_account).await, 100);
+    }
+}
+
+#[tokio::test]
+async fn chunk_past_the_last_transfer() {
+    let mut batch = funded_wallet().await;
+    let payer = batch.test_wallet.payer();
+    let transfers = batch.transfers(100, 0)[..1].to_vec();
+    let proposal = batch.propose(transfers).await.unwrap();
+
+    let ix = batch.execute_ix(&proposal, &batch.receive_accounts[..2]);
+    let error = batch
+        .test_wallet
+        .process(&[ix], &[&payer])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::InvalidBatchChunk);
+}
+
+#[tokio::test]
+async fn entry_with_wrong_decimals() {
+    let mut batch = funded_wallet().await;
+    let error = batch.propose(batch.transfers(100, 6)).await.unwrap_err();
+    assert_wallet_error(error, WalletError::DecimalsMismatch);
+}
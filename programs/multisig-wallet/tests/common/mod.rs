@@ -0,0 +1,749 @@
+#![allow(dead_code)]
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    system_program,
+};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{
+    GuardianSet, Len, Proposal, ProposalType, Recovery, VoteCount, WalletAuth, WalletConfig,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    instruction::InstructionError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+pub use anchor_spl::token::spl_token;
+
+// anchor ties the lifetime of the accounts to the lifetime of the slice
+fn process_instruction<'a>(
+    program_id: &Pubkey,
+    accounts: &[anchor_lang::prelude::AccountInfo<'a>],
+    data: &[u8],
+) -> anchor_lang::solana_program::entrypoint::ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    multisig_wallet::entry(program_id, accounts, data)
+}
+
+// runs the program natively, the work of the program itself is only metered for the sbf build
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new(
+        "multisig_wallet",
+        multisig_wallet::ID,
+        processor!(process_instruction),
+    )
+}
+
+// asserts that the first instruction of a transaction failed with the wallet error
+#[track_caller]
+pub fn assert_wallet_error(error: BanksClientError, expected: WalletError) {
+    match error {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code),
+        )) => assert_eq!(code, u32::from(expected), "expected {:?}", expected),
+        error => panic!("expected {:?}, got {:?}", expected, error),
+    }
+}
+
+// asserts that the first instruction of a transaction failed with the anchor error
+#[track_caller]
+pub fn assert_anchor_error(error: BanksClientError, expected: ErrorCode) {
+    match error {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(code),
+        )) => assert_eq!(code, u32::from(expected), "expected {:?}", expected),
+        error => panic!("expected {:?}, got {:?}", expected, error),
+    }
+}
+
+pub fn wallet_address(create_key: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"wallet", create_key.as_ref()], &multisig_wallet::ID).0
+}
+
+pub fn wallet_auth_address(wallet: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"owner", wallet.as_ref(), owner.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+pub fn authority_address(wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"authority", wallet.as_ref()], &multisig_wallet::ID).0
+}
+
+pub fn proposal_address(wallet: &Pubkey, index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"proposal", wallet.as_ref(), &index.to_le_bytes()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+pub fn vote_count_address(wallet: &Pubkey, proposal: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"votes", wallet.as_ref(), proposal.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+pub fn delegation_address(wallet: &Pubkey, delegator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"delegation", wallet.as_ref(), delegator.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+pub fn guardian_set_address(wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"guardians", wallet.as_ref()], &multisig_wallet::ID).0
+}
+
+pub fn recovery_address(wallet: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"recovery", wallet.as_ref(), owner.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+// proposal instruction with the optional accounts left out
+pub fn address_book_address(wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"address_book", wallet.as_ref()], &multisig_wallet::ID).0
+}
+
+pub fn transfer_tiers_address(wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"transfer_tiers", wallet.as_ref()], &multisig_wallet::ID).0
+}
+
+pub fn create_proposal_ix(
+    wallet: &Pubkey,
+    user: &Pubkey,
+    index: u64,
+    proposal: ProposalType,
+) -> Instruction {
+    let proposal_key = proposal_address(wallet, index);
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CreateProposal {
+            user: *user,
+            wallet: *wallet,
+            wallet_auth: wallet_auth_address(wallet, user),
+            proposal: proposal_key,
+            vote_count: vote_count_address(wallet, &proposal_key),
+            system_program: system_program::ID,
+            receive_account: None,
+            mint: None,
+            address_book: None,
+            transfer_tiers: None,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CreateProposal {
+            proposal,
+            keep_record: false,
+            draft: false,
+            auto_approve: true,
+        }
+        .data(),
+    }
+}
+
+pub fn vote_ix(wallet: &Pubkey, user: &Pubkey, proposal: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::Voting {
+            user: *user,
+            wallet: *wallet,
+            wallet_auth: wallet_auth_address(wallet, user),
+            proposal: *proposal,
+            vote_count: vote_count_address(wallet, proposal),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::Vote { include_own: true }.data(),
+    }
+}
+
+// executes an AddOwner proposal, the proposer pays for the owner account
+pub fn add_owner_ix(
+    wallet: &Pubkey,
+    proposal: &Pubkey,
+    proposer: &Pubkey,
+    user: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::AddOwner {
+            wallet: *wallet,
+            proposal: *proposal,
+            vote_count: vote_count_address(wallet, proposal),
+            proposer: *proposer,
+            payer: *proposer,
+            wallet_auth: wallet_auth_address(wallet, user),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::AddOwner {}.data(),
+    }
+}
+
+// executes a ChangeProposalLifetime proposal
+pub fn change_lifetime_ix(wallet: &Pubkey, proposal: &Pubkey, proposer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ChangeLifetime {
+            wallet: *wallet,
+            proposal: *proposal,
+            vote_count: vote_count_address(wallet, proposal),
+            proposer: *proposer,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ChangeLifetime {}.data(),
+    }
+}
+
+pub struct TestWallet {
+    pub context: ProgramTestContext,
+    pub wallet: Pubkey,
+    pub owners: Vec<Keypair>,
+    pub proposal_count: u64,
+}
+
+impl TestWallet {
+    // creates an m out of n wallet, the payer of the context is the first owner
+    pub async fn new(program_test: ProgramTest, m: u8, n: u8) -> Self {
+        let context = program_test.start_with_context().await;
+        let create_key = Keypair::new();
+        let wallet = wallet_address(&create_key.pubkey());
+        let mut owners = vec![context.payer.insecure_clone()];
+        owners.extend((1..n).map(|_| Keypair::new()));
+        let other_owners: Vec<Pubkey> = owners[1..].iter().map(|owner| owner.pubkey()).collect();
+        let mut accounts = multisig_wallet::accounts::CreateWallet {
+            user: context.payer.pubkey(),
+            create_key: create_key.pubkey(),
+            wallet,
+            wallet_auth: wallet_auth_address(&wallet, &context.payer.pubkey()),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None);
+        accounts.extend(
+            other_owners
+                .iter()
+                .map(|owner| AccountMeta::new(wallet_auth_address(&wallet, owner), false)),
+        );
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts,
+            data: multisig_wallet::instruction::CreateWallet {
+                name: "test wallet".to_string(),
+                m,
+                n,
+                owners: other_owners.clone(),
+                proposal_lifetime: 24 * 60 * 60,
+            }
+            .data(),
+        };
+        let payer = context.payer.insecure_clone();
+        let mut test_wallet = TestWallet {
+            context,
+            wallet,
+            owners,
+            proposal_count: 0,
+        };
+        test_wallet
+            .process(&[ix], &[&payer, &create_key])
+            .await
+            .unwrap();
+        // owners other than the payer pay for their own votes and proposals
+        for owner in other_owners {
+            test_wallet.transfer_lamports(&owner, 10_000_000_000).await;
+        }
+        test_wallet
+    }
+
+    pub fn payer(&self) -> Keypair {
+        self.context.payer.insecure_clone()
+    }
+
+    pub fn authority(&self) -> Pubkey {
+        authority_address(&self.wallet)
+    }
+
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&signers[0].pubkey()),
+            signers,
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    // compute units used by the instructions, natively only the invocation and cpis are metered
+    pub async fn compute_units(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> u64 {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&signers[0].pubkey()),
+            signers,
+            blockhash,
+        );
+        let result = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        result.result.unwrap();
+        result.metadata.unwrap().compute_units_consumed
+    }
+
+    pub async fn transfer_lamports(&mut self, to: &Pubkey, lamports: u64) {
+        let payer = self.payer();
+        self.process(
+            &[system_instruction::transfer(&payer.pubkey(), to, lamports)],
+            &[&payer],
+        )
+        .await
+        .unwrap();
+    }
+
+    pub fn next_proposal(&mut self) -> Pubkey {
+        let proposal = proposal_address(&self.wallet, self.proposal_count);
+        self.proposal_count += 1;
+        proposal
+    }
+
+    // creates a proposal by the owner at index proposer, returning its address
+    pub async fn propose(
+        &mut self,
+        proposer: usize,
+        proposal_type: ProposalType,
+    ) -> Result<Pubkey, BanksClientError> {
+        let user = self.owners[proposer].insecure_clone();
+        let index = self.proposal_count;
+        let ix = create_proposal_ix(&self.wallet, &user.pubkey(), index, proposal_type);
+        self.process(&[ix], &[&user]).await?;
+        Ok(self.next_proposal())
+    }
+
+    // creates a transfer proposal, the address book and transfer tiers are passed once the
+    // wallet has them
+    pub async fn propose_transfer(
+        &mut self,
+        proposer: usize,
+        mint: &Pubkey,
+        receive_account: &Pubkey,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<Pubkey, BanksClientError> {
+        let proposal = ProposalType::Transfer {
+            token_mint: *mint,
+            receive_account: *receive_account,
+            amount,
+            decimals,
+        };
+        self.propose_with_token_accounts(proposer, proposal, mint, receive_account)
+            .await
+    }
+
+    // creates a proposal that is checked against the mint and receive account on creation
+    pub async fn propose_with_token_accounts(
+        &mut self,
+        proposer: usize,
+        proposal_type: ProposalType,
+        mint: &Pubkey,
+        receive_account: &Pubkey,
+    ) -> Result<Pubkey, BanksClientError> {
+        let user = self.owners[proposer].insecure_clone();
+        let wallet = self.wallet;
+        let address_book = address_book_address(&wallet);
+        let address_book = self.account(&address_book).await.map(|_| address_book);
+        let transfer_tiers = transfer_tiers_address(&wallet);
+        let transfer_tiers = self.account(&transfer_tiers).await.map(|_| transfer_tiers);
+        let proposal = proposal_address(&wallet, self.proposal_count);
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts: multisig_wallet::accounts::CreateProposal {
+                user: user.pubkey(),
+                wallet,
+                wallet_auth: wallet_auth_address(&wallet, &user.pubkey()),
+                proposal,
+                vote_count: vote_count_address(&wallet, &proposal),
+                system_program: system_program::ID,
+                receive_account: Some(*receive_account),
+                mint: Some(*mint),
+                address_book,
+                transfer_tiers,
+            }
+            .to_account_metas(None),
+            data: multisig_wallet::instruction::CreateProposal {
+                proposal: proposal_type,
+                keep_record: false,
+                draft: false,
+                auto_approve: true,
+            }
+            .data(),
+        };
+        self.process(&[ix], &[&user]).await?;
+        Ok(self.next_proposal())
+    }
+
+    // executes a transfer proposal with the token program owning the mint
+    pub async fn execute_transfer(
+        &mut self,
+        proposal: &Pubkey,
+        send_account: &Pubkey,
+        receive_account: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let payer = self.payer();
+        let wallet = self.wallet;
+        let proposer = self.proposal(proposal).await.unwrap().proposer;
+        let token_program = self.account(mint).await.unwrap().owner;
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts: multisig_wallet::accounts::TransferFunds {
+                wallet,
+                proposal: *proposal,
+                vote_count: vote_count_address(&wallet, proposal),
+                proposer,
+                send_account: *send_account,
+                receive_account: *receive_account,
+                mint: *mint,
+                wallet_authority: self.authority(),
+                token_program,
+            }
+            .to_account_metas(None),
+            data: multisig_wallet::instruction::Transfer {}.data(),
+        };
+        self.process(&[ix], &[&payer]).await
+    }
+
+    pub async fn vote(&mut self, owner: usize, proposal: &Pubkey) -> Result<(), BanksClientError> {
+        let user = self.owners[owner].insecure_clone();
+        let ix = vote_ix(&self.wallet, &user.pubkey(), proposal);
+        self.process(&[ix], &[&user]).await
+    }
+
+    pub async fn account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn wallet_auth(&mut self, owner: &Pubkey) -> Option<WalletAuth> {
+        let address = wallet_auth_address(&self.wallet, owner);
+        let account = self.account(&address).await?;
+        Some(WalletAuth::try_deserialize(&mut account.data.as_slice()).unwrap())
+    }
+
+    pub async fn wallet_config(&mut self) -> WalletConfig {
+        let wallet = self.wallet;
+        let account = self.account(&wallet).await.unwrap();
+        *bytemuck::from_bytes::<WalletConfig>(&account.data[8..WalletConfig::len()])
+    }
+
+    pub async fn proposal(&mut self, address: &Pubkey) -> Option<Proposal> {
+        let account = self.account(address).await?;
+        Some(Proposal::try_deserialize(&mut account.data.as_slice()).unwrap())
+    }
+
+    pub async fn vote_count(&mut self, proposal: &Pubkey) -> VoteCount {
+        let address = vote_count_address(&self.wallet, proposal);
+        let account = self.account(&address).await.unwrap();
+        *bytemuck::from_bytes::<VoteCount>(&account.data[8..VoteCount::len()])
+    }
+
+    // the owner at index owner leaves the wallet, the wallet needs another owner
+    pub async fn give_up_ownership(&mut self, owner: usize) -> Result<(), BanksClientError> {
+        let user = self.owners[owner].insecure_clone();
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts: multisig_wallet::accounts::GiveUpOwnership {
+                user: user.pubkey(),
+                wallet: self.wallet,
+                wallet_auth: wallet_auth_address(&self.wallet, &user.pubkey()),
+                wallet_authority: None,
+                token_program: None,
+            }
+            .to_account_metas(None),
+            data: multisig_wallet::instruction::GiveUpOwnership {}.data(),
+        };
+        self.process(&[ix], &[&user]).await
+    }
+
+    // replaces the key of the owner at index owner, the new key pays for its owner account
+    pub async fn rotate_owner_key(
+        &mut self,
+        owner: usize,
+        new_owner: Keypair,
+    ) -> Result<(), BanksClientError> {
+        self.transfer_lamports(&new_owner.pubkey(), 1_000_000_000)
+            .await;
+        let user = self.owners[owner].insecure_clone();
+        let recovery = recovery_address(&self.wallet, &user.pubkey());
+        let recovery_initiator = self.account(&recovery).await.map(|account| {
+            Recovery::try_deserialize(&mut account.data.as_slice())
+                .unwrap()
+                .initiator
+        });
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts: multisig_wallet::accounts::RotateOwnerKey {
+                user: user.pubkey(),
+                new_user: new_owner.pubkey(),
+                wallet: self.wallet,
+                wallet_auth: wallet_auth_address(&self.wallet, &user.pubkey()),
+                new_wallet_auth: wallet_auth_address(&self.wallet, &new_owner.pubkey()),
+                delegation: delegation_address(&self.wallet, &user.pubkey()),
+                recovery,
+                recovery_initiator,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: multisig_wallet::instruction::RotateOwnerKey {}.data(),
+        };
+        self.process(&[ix], &[&user, &new_owner]).await?;
+        self.owners[owner] = new_owner;
+        Ok(())
+    }
+
+    // delegates the votes of the owner at index delegator for an hour
+    pub async fn delegate(&mut self, delegator: usize, delegate: usize) {
+        let wallet = self.wallet;
+        let user = self.owners[delegator].insecure_clone();
+        let delegate = self.owners[delegate].pubkey();
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts: multisig_wallet::accounts::DelegateVote {
+                user: user.pubkey(),
+                wallet,
+                wallet_auth: wallet_auth_address(&wallet, &user.pubkey()),
+                delegate_auth: wallet_auth_address(&wallet, &delegate),
+                delegation: delegation_address(&wallet, &user.pubkey()),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: multisig_wallet::instruction::DelegateVote {
+                delegate,
+                expiry: clock.unix_timestamp + 60 * 60,
+            }
+            .data(),
+        };
+        self.process(&[ix], &[&user]).await.unwrap();
+    }
+
+    // sets up funded guardians through a proposal of the first owner, the wallet has to be
+    // 1 out of n
+    pub async fn set_guardians(&mut self, count: usize, threshold: u8) -> Vec<Keypair> {
+        let wallet = self.wallet;
+        let payer = self.payer();
+        let guardians: Vec<Keypair> = (0..count).map(|_| Keypair::new()).collect();
+        for guardian in &guardians {
+            self.transfer_lamports(&guardian.pubkey(), 1_000_000_000)
+                .await;
+        }
+        let proposal = self
+            .propose(
+                0,
+                ProposalType::SetGuardians {
+                    guardians: guardians.iter().map(|guardian| guardian.pubkey()).collect(),
+                    threshold,
+                    recovery_delay: GuardianSet::MIN_RECOVERY_DELAY,
+                },
+            )
+            .await
+            .unwrap();
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts: multisig_wallet::accounts::SetGuardians {
+                wallet,
+                proposal,
+                vote_count: vote_count_address(&wallet, &proposal),
+                proposer: payer.pubkey(),
+                payer: payer.pubkey(),
+                guardian_set: guardian_set_address(&wallet),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: multisig_wallet::instruction::SetGuardians {}.data(),
+        };
+        self.process(&[ix], &[&payer]).await.unwrap();
+        guardians
+    }
+
+    pub async fn initiate_recovery(
+        &mut self,
+        guardian: &Keypair,
+        owner: &Pubkey,
+        new_owner: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let wallet = self.wallet;
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts: multisig_wallet::accounts::InitiateRecovery {
+                user: guardian.pubkey(),
+                wallet,
+                guardian_set: guardian_set_address(&wallet),
+                wallet_auth: wallet_auth_address(&wallet, owner),
+                recovery: recovery_address(&wallet, owner),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: multisig_wallet::instruction::InitiateRecovery {
+                new_owner: *new_owner,
+            }
+            .data(),
+        };
+        self.process(&[ix], &[guardian]).await
+    }
+
+    // pause by the owner at index user, co-signed by the given owners
+    pub async fn pause(
+        &mut self,
+        user: usize,
+        co_signers: &[usize],
+    ) -> Result<(), BanksClientError> {
+        let wallet = self.wallet;
+        let user = self.owners[user].insecure_clone();
+        let mut accounts = multisig_wallet::accounts::PauseWallet {
+            user: user.pubkey(),
+            wallet,
+            wallet_auth: wallet_auth_address(&wallet, &user.pubkey()),
+        }
+        .to_account_metas(None);
+        let mut signers = vec![user];
+        for co_signer in co_signers {
+            let co_signer = self.owners[*co_signer].insecure_clone();
+            accounts.push(AccountMeta::new_readonly(co_signer.pubkey(), true));
+            accounts.push(AccountMeta::new_readonly(
+                wallet_auth_address(&wallet, &co_signer.pubkey()),
+                false,
+            ));
+            signers.push(co_signer);
+        }
+        let ix = Instruction {
+            program_id: multisig_wallet::ID,
+            accounts,
+            data: multisig_wallet::instruction::PauseWallet {}.data(),
+        };
+        let signers: Vec<_> = signers.iter().collect();
+        self.process(&[ix], &signers).await
+    }
+
+    // moves the clock forward, proposals expire after a day
+    pub async fn advance_clock(&mut self, seconds: i64) {
+        let mut clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let payer = self.payer();
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_mint2(
+                    &spl_token::ID,
+                    &mint.pubkey(),
+                    &payer.pubkey(),
+                    None,
+                    decimals,
+                )
+                .unwrap(),
+            ],
+            &[&payer, &mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    pub async fn create_token_account(&mut self, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        let payer = self.payer();
+        let account = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &account.pubkey(),
+                    rent.minimum_balance(spl_token::state::Account::LEN),
+                    spl_token::state::Account::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_account3(
+                    &spl_token::ID,
+                    &account.pubkey(),
+                    mint,
+                    owner,
+                )
+                .unwrap(),
+            ],
+            &[&payer, &account],
+        )
+        .await
+        .unwrap();
+        account.pubkey()
+    }
+
+    pub async fn mint_to(&mut self, mint: &Pubkey, account: &Pubkey, amount: u64) {
+        let payer = self.payer();
+        self.process(
+            &[spl_token::instruction::mint_to(
+                &spl_token::ID,
+                mint,
+                account,
+                &payer.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap()],
+            &[&payer],
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn token_balance(&mut self, account: &Pubkey) -> u64 {
+        let account = self.account(account).await.unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+}
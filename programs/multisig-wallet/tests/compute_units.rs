@@ -0,0 +1,72 @@
+mod common;
+
+use common::{add_owner_ix, create_proposal_ix, vote_ix, TestWallet};
+use multisig_wallet::state::ProposalType;
+use solana_program_test::ProgramTest;
+use solana_sdk::signature::{Keypair, Signer};
+
+// upper bounds of (create_proposal, vote, add_owner) recorded with the native build, which
+// only meters the fixed cost of invoking the program and its cpis, so these catch added cpis
+// but not the bitmap work that grows with the owners
+const NATIVE_BASELINE: [u64; 3] = [675, 141, 805];
+
+// compute units of the bitmap heavy paths, run against the sbf build with
+// `anchor build && SBF_OUT_DIR=$PWD/target/deploy cargo test --test compute_units -- --nocapture`
+// from the workspace root, which prints the units instead of checking the native baseline
+#[tokio::test]
+async fn compute_units() {
+    let sbf = std::env::var_os("SBF_OUT_DIR").is_some();
+    for owners in [3, 10] {
+        let program_test = if sbf {
+            let mut program_test = ProgramTest::new("multisig_wallet", multisig_wallet::ID, None);
+            program_test.prefer_bpf(true);
+            program_test
+        } else {
+            common::program_test()
+        };
+        let mut test_wallet = TestWallet::new(program_test, 2, owners).await;
+        let user = Keypair::new().pubkey();
+        let proposer = test_wallet.owners[0].insecure_clone();
+        let voter = test_wallet.owners[owners as usize - 1].insecure_clone();
+        let create_proposal = test_wallet
+            .compute_units(
+                &[create_proposal_ix(
+                    &test_wallet.wallet,
+                    &proposer.pubkey(),
+                    0,
+                    ProposalType::AddOwner { user },
+                )],
+                &[&proposer],
+            )
+            .await;
+        let proposal = test_wallet.next_proposal();
+        let vote = test_wallet
+            .compute_units(
+                &[vote_ix(&test_wallet.wallet, &voter.pubkey(), &proposal)],
+                &[&voter],
+            )
+            .await;
+        let add_owner = test_wallet
+            .compute_units(
+                &[add_owner_ix(
+                    &test_wallet.wallet,
+                    &proposal,
+                    &proposer.pubkey(),
+                    &user,
+                )],
+                &[&proposer],
+            )
+            .await;
+        println!(
+            "{owners} owners: create_proposal {create_proposal}, vote {vote}, add_owner {add_owner}"
+        );
+        if !sbf {
+            for (units, bound) in [create_proposal, vote, add_owner]
+                .iter()
+                .zip(NATIVE_BASELINE)
+            {
+                assert!(*units <= bound, "{units} compute units exceed {bound}");
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_anchor_error, wallet_address, wallet_auth_address, TestWallet};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+fn create_wallet_ix(user: &Pubkey, create_key: &Pubkey, wallet: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CreateWallet {
+            user: *user,
+            create_key: *create_key,
+            wallet: *wallet,
+            wallet_auth: wallet_auth_address(wallet, user),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CreateWallet {
+            name: "test wallet".to_string(),
+            m: 1,
+            n: 1,
+            owners: vec![],
+            proposal_lifetime: 24 * 60 * 60,
+        }
+        .data(),
+    }
+}
+
+#[tokio::test]
+async fn wallet_derived_from_create_key() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 1).await;
+    let payer = test_wallet.payer();
+    let create_key = Keypair::new();
+    let wallet = wallet_address(&create_key.pubkey());
+    let ix = create_wallet_ix(&payer.pubkey(), &create_key.pubkey(), &wallet);
+    test_wallet
+        .process(&[ix], &[&payer, &create_key])
+        .await
+        .unwrap();
+
+    test_wallet.wallet = wallet;
+    let config = test_wallet.wallet_config().await;
+    assert_eq!(config.create_key, create_key.pubkey());
+    let (_, bump) = Pubkey::find_program_address(
+        &[b"wallet", create_key.pubkey().as_ref()],
+        &multisig_wallet::ID,
+    );
+    assert_eq!(config.bump, bump);
+}
+
+#[tokio::test]
+async fn wallet_not_derived_from_create_key() {
+    let mut context = common::program_test().start_with_context().await;
+    let create_key = Keypair::new();
+    let wallet = wallet_address(&Keypair::new().pubkey());
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_wallet_ix(
+            &context.payer.pubkey(),
+            &create_key.pubkey(),
+            &wallet,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &create_key],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err();
+    assert_anchor_error(error, ErrorCode::ConstraintSeeds);
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    assert_wallet_error, delegation_address, vote_count_address, wallet_auth_address, TestWallet,
+};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::ProposalType;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+// vote of the owner at index user, casting the votes of the given delegators as well
+fn delegated_vote_ix(
+    test_wallet: &TestWallet,
+    user: usize,
+    proposal: &Pubkey,
+    delegators: &[usize],
+    include_own: bool,
+) -> Instruction {
+    let wallet = test_wallet.wallet;
+    let user = test_wallet.owners[user].pubkey();
+    let mut accounts = multisig_wallet::accounts::Voting {
+        user,
+        wallet,
+        wallet_auth: wallet_auth_address(&wallet, &user),
+        proposal: *proposal,
+        vote_count: vote_count_address(&wallet, proposal),
+    }
+    .to_account_metas(None);
+    for delegator in delegators {
+        let delegator = test_wallet.owners[*delegator].pubkey();
+        accounts.push(AccountMeta::new_readonly(
+            delegation_address(&wallet, &delegator),
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            wallet_auth_address(&wallet, &delegator),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts,
+        data: multisig_wallet::instruction::Vote { include_own }.data(),
+    }
+}
+
+#[tokio::test]
+async fn delegate_votes_without_own_vote() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 3, 3).await;
+    test_wallet.delegate(1, 2).await;
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::ChangeProposalLifetime {
+                duration: 2 * 24 * 60 * 60,
+            },
+        )
+        .await
+        .unwrap();
+    let user = test_wallet.owners[2].insecure_clone();
+
+    // without delegators there is nothing to vote for
+    let ix = delegated_vote_ix(&test_wallet, 2, &proposal, &[], false);
+    let error = test_wallet.process(&[ix], &[&user]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::InsufficientAccounts);
+
+    let ix = delegated_vote_ix(&test_wallet, 2, &proposal, &[1], false);
+    test_wallet.process(&[ix], &[&user]).await.unwrap();
+    let votes = test_wallet.vote_count(&proposal).await;
+    assert_eq!((votes.votes, votes.weight), (2, 2));
+
+    // the delegate can still cast its own vote afterwards
+    test_wallet.vote(2, &proposal).await.unwrap();
+    let votes = test_wallet.vote_count(&proposal).await;
+    assert_eq!((votes.votes, votes.weight), (3, 3));
+}
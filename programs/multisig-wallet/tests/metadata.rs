@@ -0,0 +1,75 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use common::{assert_wallet_error, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{ProposalType, WalletMetadata};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+fn metadata_address(wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata", wallet.as_ref()], &multisig_wallet::ID).0
+}
+
+#[tokio::test]
+async fn update_metadata() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::UpdateMetadata {
+                name: Some("treasury".to_string()),
+                description: Some("team treasury".to_string()),
+                icon_uri: None,
+            },
+        )
+        .await
+        .unwrap();
+    let metadata = metadata_address(&wallet);
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::UpdateMetadata {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            payer: payer.pubkey(),
+            metadata,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::UpdateMetadata {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+
+    let config = test_wallet.wallet_config().await;
+    assert_eq!(&config.name[..9], b"treasury\0");
+    let account = test_wallet.account(&metadata).await.unwrap();
+    assert_eq!(
+        account.data.len(),
+        WalletMetadata::space("team treasury", "")
+    );
+    let metadata = WalletMetadata::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(metadata.wallet, wallet);
+    assert_eq!(metadata.description, "team treasury");
+    assert!(metadata.icon_uri.is_empty());
+}
+
+#[tokio::test]
+async fn name_longer_than_the_config_fits() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let error = test_wallet
+        .propose(
+            0,
+            ProposalType::UpdateMetadata {
+                name: Some("a".repeat(20)),
+                description: None,
+                icon_uri: None,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::InvalidMetadata);
+}
@@ -0,0 +1,317 @@
+mod common;
+
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    system_program,
+};
+use anchor_lang::{AnchorSerialize, Discriminator, InstructionData, ToAccountMetas};
+use common::{assert_wallet_error, create_proposal_ix, vote_count_address, wallet_auth_address};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{
+    LegacyProposal, LegacyWalletAuth, LegacyWalletConfig, Len, Proposal, ProposalType, VoteCount,
+    WalletAuth, WalletConfig, ACCOUNT_VERSION,
+};
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+fn legacy_account(data: Vec<u8>, len: usize) -> Account {
+    let mut account = Account::new(1_000_000_000, len, &multisig_wallet::ID);
+    account.data[..data.len()].copy_from_slice(&data);
+    account
+}
+
+fn add_legacy_wallet(program_test: &mut ProgramTest, wallet: &Pubkey, owners: &[Pubkey]) {
+    let mut owner_identities = [0u8; 32];
+    owner_identities[0] = 0b1110_0000;
+    let legacy = LegacyWalletConfig {
+        discriminator: WalletConfig::DISCRIMINATOR,
+        name: "legacy".to_string(),
+        m: 2,
+        n: 3,
+        owners: 3,
+        owner_identities,
+        proposal_lifetime: 3600,
+    };
+    program_test.add_account(
+        *wallet,
+        legacy_account(legacy.try_to_vec().unwrap(), LegacyWalletConfig::LEN),
+    );
+    for (id, owner) in owners.iter().enumerate() {
+        // the creator got the right discriminator, the other owners a mistyped one
+        let discriminator = if id == 0 {
+            WalletAuth::DISCRIMINATOR
+        } else {
+            LegacyWalletAuth::MISTYPED_DISCRIMINATOR
+        };
+        let legacy = LegacyWalletAuth {
+            discriminator,
+            owner: *owner,
+            wallet: *wallet,
+            id: id as u8,
+            added_time: 100,
+        };
+        program_test.add_account(
+            wallet_auth_address(wallet, owner),
+            legacy_account(legacy.try_to_vec().unwrap(), LegacyWalletAuth::LEN),
+        );
+    }
+}
+
+// adds a proposal of the old layout with its vote count, returns the proposal address
+fn add_legacy_proposal(
+    program_test: &mut ProgramTest,
+    wallet: &Pubkey,
+    proposer: &Pubkey,
+) -> Pubkey {
+    let proposal = Keypair::new().pubkey();
+    let legacy = LegacyProposal {
+        discriminator: Proposal::DISCRIMINATOR,
+        wallet: *wallet,
+        proposer: *proposer,
+    };
+    program_test.add_account(
+        proposal,
+        legacy_account(legacy.try_to_vec().unwrap(), LegacyProposal::LEN),
+    );
+    program_test.add_account(
+        vote_count_address(wallet, &proposal),
+        legacy_account(
+            VoteCount::DISCRIMINATOR.to_vec(),
+            LegacyProposal::VOTE_COUNT_LEN,
+        ),
+    );
+    proposal
+}
+
+fn close_legacy_proposal_ix(wallet: &Pubkey, proposal: &Pubkey, proposer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CloseLegacyProposal {
+            proposer: *proposer,
+            wallet: *wallet,
+            proposal: *proposal,
+            vote_count: vote_count_address(wallet, proposal),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CloseLegacyProposal {}.data(),
+    }
+}
+
+fn migrate_ix(payer: &Pubkey, wallet: &Pubkey, accounts: &[Pubkey]) -> Instruction {
+    let mut metas = multisig_wallet::accounts::MigrateWallet {
+        payer: *payer,
+        wallet: *wallet,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    metas.extend(
+        accounts
+            .iter()
+            .map(|account| AccountMeta::new(*account, false)),
+    );
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: metas,
+        data: multisig_wallet::instruction::MigrateWallet {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn migrates_legacy_wallet() {
+    let mut program_test = common::program_test();
+    let wallet = Keypair::new().pubkey();
+    let owners: Vec<Pubkey> = (0..3).map(|_| Keypair::new().pubkey()).collect();
+    add_legacy_wallet(&mut program_test, &wallet, &owners);
+    let mut context = program_test.start_with_context().await;
+
+    let wallet_auths: Vec<Pubkey> = owners
+        .iter()
+        .map(|owner| wallet_auth_address(&wallet, owner))
+        .collect();
+    let transaction = Transaction::new_signed_with_payer(
+        &[migrate_ix(&context.payer.pubkey(), &wallet, &wallet_auths)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(wallet)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), WalletConfig::space(256));
+    let config = bytemuck::from_bytes::<WalletConfig>(&account.data[8..WalletConfig::len()]);
+    assert_eq!(config.version, ACCOUNT_VERSION);
+    assert_eq!(&config.name[..6], b"legacy");
+    assert_eq!((config.m, config.n, config.owners), (2, 3, 3));
+    assert_eq!(config.total_weight, 3);
+    assert_eq!(config.proposal_lifetime, 3600);
+    assert_eq!(account.data[WalletConfig::len()], 0b1110_0000);
+
+    for (id, address) in wallet_auths.iter().enumerate() {
+        let account = context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .unwrap();
+        let wallet_auth = <WalletAuth as anchor_lang::AccountDeserialize>::try_deserialize(
+            &mut &account.data[..],
+        )
+        .unwrap();
+        assert_eq!(wallet_auth.owner, owners[id]);
+        assert_eq!(wallet_auth.wallet, wallet);
+        assert_eq!(wallet_auth.id, id as u16);
+        assert_eq!(wallet_auth.weight, 1);
+        assert_eq!(wallet_auth.version, ACCOUNT_VERSION);
+    }
+}
+
+#[tokio::test]
+async fn rejects_accounts_of_other_wallets() {
+    let mut program_test = common::program_test();
+    let wallet = Keypair::new().pubkey();
+    let other_wallet = Keypair::new().pubkey();
+    let owner = Keypair::new().pubkey();
+    add_legacy_wallet(&mut program_test, &wallet, &[]);
+    add_legacy_wallet(&mut program_test, &other_wallet, &[owner]);
+    let mut context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[migrate_ix(
+            &context.payer.pubkey(),
+            &wallet,
+            &[wallet_auth_address(&other_wallet, &owner)],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::InvalidMigrationAccount);
+}
+
+#[tokio::test]
+async fn closes_legacy_proposal() {
+    let mut program_test = common::program_test();
+    let wallet = Keypair::new().pubkey();
+    let proposer = Keypair::new().pubkey();
+    add_legacy_wallet(&mut program_test, &wallet, &[proposer]);
+    let proposal = add_legacy_proposal(&mut program_test, &wallet, &proposer);
+    let mut context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[close_legacy_proposal_ix(&wallet, &proposal, &proposer)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let banks_client = &mut context.banks_client;
+    assert!(banks_client.get_account(proposal).await.unwrap().is_none());
+    let vote_count = vote_count_address(&wallet, &proposal);
+    assert!(banks_client
+        .get_account(vote_count)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        banks_client.get_balance(proposer).await.unwrap(),
+        2_000_000_000
+    );
+}
+
+#[tokio::test]
+async fn legacy_proposal_rent_goes_to_its_proposer() {
+    let mut program_test = common::program_test();
+    let wallet = Keypair::new().pubkey();
+    let proposer = Keypair::new().pubkey();
+    add_legacy_wallet(&mut program_test, &wallet, &[proposer]);
+    let proposal = add_legacy_proposal(&mut program_test, &wallet, &proposer);
+    let mut context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[close_legacy_proposal_ix(
+            &wallet,
+            &proposal,
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::InvalidMigrationAccount);
+}
+
+#[tokio::test]
+async fn unmigrated_wallet_is_rejected() {
+    let mut program_test = common::program_test();
+    let wallet = Keypair::new().pubkey();
+    let owner = Keypair::new();
+    add_legacy_wallet(&mut program_test, &wallet, &[]);
+    // the owner account is already migrated, only the wallet is left in the old layout
+    let wallet_auth = WalletAuth {
+        owner: owner.pubkey(),
+        wallet,
+        id: 0,
+        added_time: 100,
+        weight: 1,
+        version: ACCOUNT_VERSION,
+        reserved: [0; 32],
+    };
+    let mut data = WalletAuth::DISCRIMINATOR.to_vec();
+    data.extend(wallet_auth.try_to_vec().unwrap());
+    program_test.add_account(
+        wallet_auth_address(&wallet, &owner.pubkey()),
+        legacy_account(data, WalletAuth::len()),
+    );
+    program_test.add_account(
+        owner.pubkey(),
+        Account::new(1_000_000_000, 0, &system_program::ID),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_proposal_ix(
+            &wallet,
+            &owner.pubkey(),
+            0,
+            ProposalType::ChangeProposalLifetime { duration: 7200 },
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &owner],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::AccountNotMigrated);
+}
@@ -0,0 +1,219 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{spl_token, vote_count_address, TestWallet};
+use multisig_wallet::state::ProposalType;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+struct Trade {
+    test_wallet: TestWallet,
+    counterparty: Keypair,
+    give_mint: Pubkey,
+    want_mint: Pubkey,
+    // wallet accounts
+    send_account: Pubkey,
+    receive_account: Pubkey,
+    // counterparty accounts
+    counterparty_send_account: Pubkey,
+    counterparty_receive_account: Pubkey,
+    trade: Pubkey,
+    escrow: Pubkey,
+}
+
+fn trade_address(wallet: &Pubkey, proposal: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"otc_trade", wallet.as_ref(), proposal.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+fn escrow_address(trade: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"escrow", trade.as_ref()], &multisig_wallet::ID).0
+}
+
+// the wallet escrows 100 of the give mint for 50 of the want mint of the counterparty
+async fn escrowed_trade() -> Trade {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let counterparty = Keypair::new();
+    test_wallet
+        .transfer_lamports(&counterparty.pubkey(), 1_000_000_000)
+        .await;
+    let authority = test_wallet.authority();
+    let give_mint = test_wallet.create_mint(6).await;
+    let want_mint = test_wallet.create_mint(9).await;
+    let send_account = test_wallet
+        .create_token_account(&give_mint, &authority)
+        .await;
+    let receive_account = test_wallet
+        .create_token_account(&want_mint, &authority)
+        .await;
+    let counterparty_send_account = test_wallet
+        .create_token_account(&want_mint, &counterparty.pubkey())
+        .await;
+    let counterparty_receive_account = test_wallet
+        .create_token_account(&give_mint, &counterparty.pubkey())
+        .await;
+    test_wallet.mint_to(&give_mint, &send_account, 100).await;
+    test_wallet
+        .mint_to(&want_mint, &counterparty_send_account, 50)
+        .await;
+
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::OtcTrade {
+                give_mint,
+                give_amount: 100,
+                want_mint,
+                want_amount: 50,
+                counterparty: counterparty.pubkey(),
+            },
+        )
+        .await
+        .unwrap();
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let trade = trade_address(&wallet, &proposal);
+    let escrow = escrow_address(&trade);
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::EscrowOtcTrade {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            payer: payer.pubkey(),
+            trade,
+            escrow,
+            send_account,
+            give_mint,
+            wallet_authority: authority,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::EscrowOtcTrade {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    assert_eq!(test_wallet.token_balance(&escrow).await, 100);
+    assert_eq!(test_wallet.token_balance(&send_account).await, 0);
+    Trade {
+        test_wallet,
+        counterparty,
+        give_mint,
+        want_mint,
+        send_account,
+        receive_account,
+        counterparty_send_account,
+        counterparty_receive_account,
+        trade,
+        escrow,
+    }
+}
+
+#[tokio::test]
+async fn complete_trade() {
+    let Trade {
+        mut test_wallet,
+        counterparty,
+        give_mint,
+        want_mint,
+        receive_account,
+        counterparty_send_account,
+        counterparty_receive_account,
+        trade,
+        escrow,
+        ..
+    } = escrowed_trade().await;
+    let payer = test_wallet.payer().pubkey();
+    let payer_lamports = test_wallet.account(&payer).await.unwrap().lamports;
+    let rent = test_wallet.account(&trade).await.unwrap().lamports
+        + test_wallet.account(&escrow).await.unwrap().lamports;
+
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CompleteOtcTrade {
+            counterparty: counterparty.pubkey(),
+            wallet: test_wallet.wallet,
+            trade,
+            payer,
+            escrow,
+            counterparty_receive_account,
+            counterparty_send_account,
+            receive_account,
+            give_mint,
+            want_mint,
+            wallet_authority: test_wallet.authority(),
+            give_token_program: spl_token::ID,
+            want_token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CompleteOtcTrade {}.data(),
+    };
+    test_wallet.process(&[ix], &[&counterparty]).await.unwrap();
+
+    assert_eq!(test_wallet.token_balance(&receive_account).await, 50);
+    assert_eq!(
+        test_wallet.token_balance(&counterparty_send_account).await,
+        0
+    );
+    assert_eq!(
+        test_wallet
+            .token_balance(&counterparty_receive_account)
+            .await,
+        100
+    );
+    // the trade and its escrow are closed, the rent goes back to the payer
+    assert!(test_wallet.account(&trade).await.is_none());
+    assert!(test_wallet.account(&escrow).await.is_none());
+    assert_eq!(
+        test_wallet.account(&payer).await.unwrap().lamports,
+        payer_lamports + rent
+    );
+}
+
+#[tokio::test]
+async fn cancel_trade() {
+    let Trade {
+        mut test_wallet,
+        give_mint,
+        send_account,
+        trade,
+        escrow,
+        ..
+    } = escrowed_trade().await;
+    let proposal = test_wallet
+        .propose(0, ProposalType::CancelOtcTrade { trade })
+        .await
+        .unwrap();
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CancelOtcTrade {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            trade,
+            payer: payer.pubkey(),
+            escrow,
+            receive_account: send_account,
+            give_mint,
+            wallet_authority: test_wallet.authority(),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CancelOtcTrade {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+
+    assert_eq!(test_wallet.token_balance(&send_account).await, 100);
+    assert!(test_wallet.account(&trade).await.is_none());
+    assert!(test_wallet.account(&escrow).await.is_none());
+}
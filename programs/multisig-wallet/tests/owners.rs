@@ -0,0 +1,166 @@
+mod common;
+
+use common::{
+    add_owner_ix, assert_wallet_error, change_lifetime_ix, delegation_address, recovery_address,
+    vote_ix, TestWallet,
+};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{Len, ProposalType, WalletConfig};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+// lifetime change proposed and approved by the first owner
+async fn lifetime_proposal(test_wallet: &mut TestWallet) -> Pubkey {
+    test_wallet
+        .propose(
+            0,
+            ProposalType::ChangeProposalLifetime {
+                duration: 2 * 24 * 60 * 60,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn proposal_goes_stale_when_an_owner_leaves() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 3).await;
+    let payer = test_wallet.payer();
+    let proposal = lifetime_proposal(&mut test_wallet).await;
+    test_wallet.give_up_ownership(2).await.unwrap();
+
+    let ix = change_lifetime_ix(&test_wallet.wallet, &proposal, &payer.pubkey());
+    let error = test_wallet.process(&[ix], &[&payer]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::StaleProposal);
+}
+
+#[tokio::test]
+async fn proposal_goes_stale_when_a_key_is_rotated() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 3).await;
+    let payer = test_wallet.payer();
+    let proposal = lifetime_proposal(&mut test_wallet).await;
+    test_wallet
+        .rotate_owner_key(1, Keypair::new())
+        .await
+        .unwrap();
+
+    let ix = change_lifetime_ix(&test_wallet.wallet, &proposal, &payer.pubkey());
+    let error = test_wallet.process(&[ix], &[&payer]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::StaleProposal);
+}
+
+#[tokio::test]
+async fn votes_of_departed_owners_do_not_count() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 3).await;
+    let payer = test_wallet.payer();
+    let proposal = lifetime_proposal(&mut test_wallet).await;
+    test_wallet.vote(2, &proposal).await.unwrap();
+    test_wallet.give_up_ownership(2).await.unwrap();
+
+    // the proposal reached the threshold with the vote of the departed owner
+    let ix = change_lifetime_ix(&test_wallet.wallet, &proposal, &payer.pubkey());
+    let error = test_wallet.process(&[ix], &[&payer]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::StaleProposal);
+    let error = test_wallet.vote(1, &proposal).await.unwrap_err();
+    assert_wallet_error(error, WalletError::StaleProposal);
+
+    // the remaining owners have to approve it again
+    let proposal = lifetime_proposal(&mut test_wallet).await;
+    let ix = change_lifetime_ix(&test_wallet.wallet, &proposal, &payer.pubkey());
+    let error = test_wallet
+        .process(std::slice::from_ref(&ix), &[&payer])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::NotEnoughVotes);
+    test_wallet.vote(1, &proposal).await.unwrap();
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+}
+
+#[tokio::test]
+async fn rotated_key_keeps_the_owner_id() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 3).await;
+    let wallet = test_wallet.wallet;
+    let old_owner = test_wallet.owners[1].insecure_clone();
+    let wallet_auth = test_wallet.wallet_auth(&old_owner.pubkey()).await.unwrap();
+    let new_owner = Keypair::new();
+    test_wallet
+        .rotate_owner_key(1, new_owner.insecure_clone())
+        .await
+        .unwrap();
+    assert!(test_wallet.wallet_auth(&old_owner.pubkey()).await.is_none());
+    let new_wallet_auth = test_wallet.wallet_auth(&new_owner.pubkey()).await.unwrap();
+    assert_eq!(
+        (new_wallet_auth.id, new_wallet_auth.weight),
+        (wallet_auth.id, wallet_auth.weight)
+    );
+
+    // only the new key can vote
+    let proposal = lifetime_proposal(&mut test_wallet).await;
+    let ix = vote_ix(&wallet, &old_owner.pubkey(), &proposal);
+    assert!(test_wallet.process(&[ix], &[&old_owner]).await.is_err());
+    test_wallet.vote(1, &proposal).await.unwrap();
+    assert_eq!(test_wallet.vote_count(&proposal).await.weight, 2);
+}
+
+#[tokio::test]
+async fn rotation_closes_delegation_and_recovery() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 3).await;
+    let wallet = test_wallet.wallet;
+    let guardians = test_wallet.set_guardians(1, 1).await;
+    let owner = test_wallet.owners[1].pubkey();
+    test_wallet.delegate(1, 2).await;
+    test_wallet
+        .initiate_recovery(&guardians[0], &owner, &Keypair::new().pubkey())
+        .await
+        .unwrap();
+    let recovery = recovery_address(&wallet, &owner);
+    let recovery_rent = test_wallet.account(&recovery).await.unwrap().lamports;
+    let guardian_balance = test_wallet
+        .account(&guardians[0].pubkey())
+        .await
+        .unwrap()
+        .lamports;
+
+    test_wallet
+        .rotate_owner_key(1, Keypair::new())
+        .await
+        .unwrap();
+    assert!(test_wallet
+        .account(&delegation_address(&wallet, &owner))
+        .await
+        .is_none());
+    assert!(test_wallet.account(&recovery).await.is_none());
+    // the rent of the recovery goes back to the guardian who initiated it
+    assert_eq!(
+        test_wallet
+            .account(&guardians[0].pubkey())
+            .await
+            .unwrap()
+            .lamports,
+        guardian_balance + recovery_rent
+    );
+}
+
+#[tokio::test]
+async fn add_owner_past_a_byte_of_ids() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 8).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let wallet_len = test_wallet.account(&wallet).await.unwrap().data.len();
+    let user = Keypair::new().pubkey();
+    let proposal = test_wallet
+        .propose(0, ProposalType::AddOwner { user })
+        .await
+        .unwrap();
+    let ix = add_owner_ix(&wallet, &proposal, &payer.pubkey(), &user);
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+
+    // the eight ids of the first byte are taken, the bitmap grows by a byte
+    let account = test_wallet.account(&wallet).await.unwrap();
+    assert_eq!(account.data.len(), wallet_len + 1);
+    assert_eq!(account.data[WalletConfig::len()..], [u8::MAX, 0b1000_0000]);
+    assert_eq!(test_wallet.wallet_auth(&user).await.unwrap().id, 8);
+    assert_eq!(test_wallet.wallet_config().await.owners, 9);
+}
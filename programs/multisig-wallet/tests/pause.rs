@@ -0,0 +1,95 @@
+mod common;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_wallet_error, change_lifetime_ix, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::ProposalType;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+// executes a proposal handled by the ChangeWalletState accounts
+fn change_wallet_state_ix(
+    test_wallet: &TestWallet,
+    proposal: &Pubkey,
+    data: Vec<u8>,
+) -> Instruction {
+    let wallet = test_wallet.wallet;
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ChangeWalletState {
+            wallet,
+            proposal: *proposal,
+            vote_count: vote_count_address(&wallet, proposal),
+            proposer: test_wallet.owners[0].pubkey(),
+        }
+        .to_account_metas(None),
+        data,
+    }
+}
+
+#[tokio::test]
+async fn paused_wallet_only_executes_unpause() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let payer = test_wallet.payer();
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::ChangeProposalLifetime {
+                duration: 2 * 24 * 60 * 60,
+            },
+        )
+        .await
+        .unwrap();
+    test_wallet.pause(1, &[]).await.unwrap();
+    assert_eq!(test_wallet.wallet_config().await.paused, 1);
+
+    let change_lifetime = change_lifetime_ix(&test_wallet.wallet, &proposal, &payer.pubkey());
+    let error = test_wallet
+        .process(std::slice::from_ref(&change_lifetime), &[&payer])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::WalletPaused);
+
+    let unpause = test_wallet.propose(0, ProposalType::Unpause).await.unwrap();
+    let ix = change_wallet_state_ix(
+        &test_wallet,
+        &unpause,
+        multisig_wallet::instruction::Unpause {}.data(),
+    );
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    assert_eq!(test_wallet.wallet_config().await.paused, 0);
+    test_wallet
+        .process(&[change_lifetime], &[&payer])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn pause_threshold() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let payer = test_wallet.payer();
+    let error = test_wallet
+        .propose(0, ProposalType::ChangePauseThreshold { threshold: 3 })
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::InvalidPauseThreshold);
+    let proposal = test_wallet
+        .propose(0, ProposalType::ChangePauseThreshold { threshold: 2 })
+        .await
+        .unwrap();
+    let ix = change_wallet_state_ix(
+        &test_wallet,
+        &proposal,
+        multisig_wallet::instruction::ChangePauseThreshold {}.data(),
+    );
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    assert_eq!(test_wallet.wallet_config().await.pause_threshold, 2);
+
+    // an owner co-signing their own pause is counted once
+    let error = test_wallet.pause(0, &[]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::NotEnoughSigners);
+    let error = test_wallet.pause(0, &[0]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::NotEnoughSigners);
+    test_wallet.pause(0, &[1]).await.unwrap();
+    assert_eq!(test_wallet.wallet_config().await.paused, 1);
+}
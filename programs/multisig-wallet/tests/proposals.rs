@@ -0,0 +1,331 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    add_owner_ix, assert_anchor_error, assert_wallet_error, change_lifetime_ix, create_proposal_ix,
+    proposal_address, vote_count_address, wallet_auth_address, TestWallet,
+};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{Proposal, ProposalStatus, ProposalType};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn add_owner_after_enough_votes() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 3).await;
+    let user = Keypair::new().pubkey();
+    let proposal = test_wallet
+        .propose(0, ProposalType::AddOwner { user })
+        .await
+        .unwrap();
+    let payer = test_wallet.payer();
+    let ix = add_owner_ix(&test_wallet.wallet, &proposal, &payer.pubkey(), &user);
+    let error = test_wallet
+        .process(std::slice::from_ref(&ix), &[&payer])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::NotEnoughVotes);
+
+    test_wallet.vote(1, &proposal).await.unwrap();
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    let wallet_auth = test_wallet.wallet_auth(&user).await.unwrap();
+    assert_eq!(wallet_auth.wallet, test_wallet.wallet);
+    assert_eq!(wallet_auth.id, 3);
+    assert_eq!(wallet_auth.weight, 1);
+    // the proposal and its votes are closed once executed
+    assert!(test_wallet.account(&proposal).await.is_none());
+}
+
+#[tokio::test]
+async fn cancel_draft() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let proposal_type = ProposalType::ChangeProposalLifetime {
+        duration: 2 * 24 * 60 * 60,
+    };
+    let mut ix = create_proposal_ix(&wallet, &payer.pubkey(), 0, proposal_type.clone());
+    ix.data = multisig_wallet::instruction::CreateProposal {
+        proposal: proposal_type,
+        keep_record: false,
+        draft: true,
+        auto_approve: true,
+    }
+    .data();
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    let proposal = test_wallet.next_proposal();
+    let vote_count = vote_count_address(&wallet, &proposal);
+
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CancelProposal {
+            user: payer.pubkey(),
+            wallet,
+            proposal,
+            vote_count,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CancelProposal {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    assert!(test_wallet.account(&proposal).await.is_none());
+    assert!(test_wallet.account(&vote_count).await.is_none());
+}
+
+#[tokio::test]
+async fn transfer_without_token_accounts() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 2).await;
+    let error = test_wallet
+        .propose(
+            0,
+            ProposalType::Transfer {
+                token_mint: Pubkey::new_unique(),
+                receive_account: Pubkey::new_unique(),
+                amount: 1,
+                decimals: 0,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::InsufficientAccounts);
+}
+
+#[tokio::test]
+async fn change_owner_weight_of_the_proposed_owner() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let owner = test_wallet.owners[1].pubkey();
+    let proposal = test_wallet
+        .propose(0, ProposalType::ChangeOwnerWeight { owner, weight: 3 })
+        .await
+        .unwrap();
+    let change_owner_weight_ix = |owner: &Pubkey| Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ChangeOwnerWeight {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            wallet_auth: wallet_auth_address(&wallet, owner),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ChangeOwnerWeight {}.data(),
+    };
+
+    let ix = change_owner_weight_ix(&payer.pubkey());
+    let error = test_wallet.process(&[ix], &[&payer]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::ProposalInstructionMismatch);
+
+    let ix = change_owner_weight_ix(&owner);
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    assert_eq!(test_wallet.wallet_auth(&owner).await.unwrap().weight, 3);
+}
+
+#[tokio::test]
+async fn finalize_expired_proposal() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::ChangeProposalLifetime {
+                duration: 2 * 24 * 60 * 60,
+            },
+        )
+        .await
+        .unwrap();
+    let finalize_ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::Finalize {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::Finalize {}.data(),
+    };
+    let error = test_wallet
+        .process(std::slice::from_ref(&finalize_ix), &[&payer])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::ProposalStillValid);
+
+    // proposals expire after the lifetime of a day
+    test_wallet.advance_clock(24 * 60 * 60 + 1).await;
+    test_wallet
+        .process(&[finalize_ix], &[&payer])
+        .await
+        .unwrap();
+    assert!(test_wallet.account(&proposal).await.is_none());
+    assert!(test_wallet
+        .account(&vote_count_address(&wallet, &proposal))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn no_approval_without_votes() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 3).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    // a third of the remaining weight of one rounds down to zero
+    test_wallet.give_up_ownership(1).await.unwrap();
+    test_wallet.give_up_ownership(2).await.unwrap();
+    let proposal_type = ProposalType::ChangeProposalLifetime {
+        duration: 2 * 24 * 60 * 60,
+    };
+    let mut ix = create_proposal_ix(&wallet, &payer.pubkey(), 0, proposal_type.clone());
+    ix.data = multisig_wallet::instruction::CreateProposal {
+        proposal: proposal_type,
+        keep_record: false,
+        draft: false,
+        auto_approve: false,
+    }
+    .data();
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    let proposal = test_wallet.next_proposal();
+
+    let votes = test_wallet.vote_count(&proposal).await;
+    assert_eq!((votes.weight, votes.threshold), (0, 1));
+    assert!(test_wallet.proposal(&proposal).await.unwrap().status == ProposalStatus::Active);
+}
+
+#[tokio::test]
+async fn threshold_of_odd_total_weight() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let owner = test_wallet.owners[1].pubkey();
+    let proposal = test_wallet
+        .propose(0, ProposalType::ChangeOwnerWeight { owner, weight: 2 })
+        .await
+        .unwrap();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ChangeOwnerWeight {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            wallet_auth: wallet_auth_address(&wallet, &owner),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ChangeOwnerWeight {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+
+    // half of a total weight of three needs a weight of two
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::ChangeProposalLifetime {
+                duration: 2 * 24 * 60 * 60,
+            },
+        )
+        .await
+        .unwrap();
+    let votes = test_wallet.vote_count(&proposal).await;
+    assert_eq!((votes.weight, votes.threshold), (1, 2));
+    assert!(test_wallet.proposal(&proposal).await.unwrap().status == ProposalStatus::Active);
+    test_wallet.vote(1, &proposal).await.unwrap();
+    assert!(test_wallet.proposal(&proposal).await.unwrap().status == ProposalStatus::Approved);
+}
+
+#[tokio::test]
+async fn type_threshold_of_unknown_type() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let error = test_wallet
+        .propose(
+            0,
+            ProposalType::SetTypeThreshold {
+                proposal_type: ProposalType::LAST_INDEX + 1,
+                m: 2,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::InvalidTypeThreshold);
+    test_wallet
+        .propose(
+            0,
+            ProposalType::SetTypeThreshold {
+                proposal_type: ProposalType::LAST_INDEX,
+                m: 2,
+            },
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn keep_executed_proposal_as_record() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let proposal_type = ProposalType::ChangeProposalLifetime {
+        duration: 2 * 24 * 60 * 60,
+    };
+    let mut ix = create_proposal_ix(&wallet, &payer.pubkey(), 0, proposal_type.clone());
+    ix.data = multisig_wallet::instruction::CreateProposal {
+        proposal: proposal_type.clone(),
+        keep_record: true,
+        draft: false,
+        auto_approve: true,
+    }
+    .data();
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    let proposal = test_wallet.next_proposal();
+    test_wallet.vote(1, &proposal).await.unwrap();
+    let ix = change_lifetime_ix(&wallet, &proposal, &payer.pubkey());
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+
+    // the record keeps the tally of the closed vote count and shrinks to its contents
+    assert!(test_wallet
+        .account(&vote_count_address(&wallet, &proposal))
+        .await
+        .is_none());
+    let record = test_wallet.proposal(&proposal).await.unwrap();
+    assert!(record.status == ProposalStatus::Executed);
+    assert_eq!((record.votes, record.weight), (2, 2));
+    let account = test_wallet.account(&proposal).await.unwrap();
+    assert_eq!(account.data.len(), record.record_space());
+    assert!(account.data.len() < Proposal::space(&proposal_type));
+    let rent = test_wallet.context.banks_client.get_rent().await.unwrap();
+    assert_eq!(account.lamports, rent.minimum_balance(account.data.len()));
+}
+
+#[tokio::test]
+async fn proposals_take_sequential_indexes() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 2).await;
+    let wallet = test_wallet.wallet;
+    for index in 0..2 {
+        let proposal = test_wallet
+            .propose(0, ProposalType::ChangeProposalLifetime { duration: 7200 })
+            .await
+            .unwrap();
+        assert_eq!(proposal, proposal_address(&wallet, index));
+        assert_eq!(test_wallet.proposal(&proposal).await.unwrap().index, index);
+    }
+    assert_eq!(test_wallet.wallet_config().await.proposal_count, 2);
+}
+
+#[tokio::test]
+async fn proposal_at_a_skipped_index() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 2, 2).await;
+    let payer = test_wallet.payer();
+    let ix = create_proposal_ix(
+        &test_wallet.wallet,
+        &payer.pubkey(),
+        1,
+        ProposalType::ChangeProposalLifetime { duration: 7200 },
+    );
+    let error = test_wallet.process(&[ix], &[&payer]).await.unwrap_err();
+    assert_anchor_error(error, ErrorCode::ConstraintSeeds);
+}
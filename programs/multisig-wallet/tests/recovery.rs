@@ -0,0 +1,291 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{
+    assert_wallet_error, change_lifetime_ix, guardian_set_address, recovery_address,
+    wallet_auth_address, TestWallet,
+};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{GuardianSet, ProposalType};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+async fn approve_recovery(
+    test_wallet: &mut TestWallet,
+    guardian: &Keypair,
+    owner: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let wallet = test_wallet.wallet;
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ApproveRecovery {
+            user: guardian.pubkey(),
+            wallet,
+            guardian_set: guardian_set_address(&wallet),
+            recovery: recovery_address(&wallet, owner),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ApproveRecovery {}.data(),
+    };
+    test_wallet.process(&[ix], &[guardian]).await
+}
+
+async fn cancel_recovery(
+    test_wallet: &mut TestWallet,
+    user: &Keypair,
+    initiator: &Pubkey,
+    owner: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let wallet = test_wallet.wallet;
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CancelRecovery {
+            user: user.pubkey(),
+            wallet,
+            guardian_set: guardian_set_address(&wallet),
+            recovery: recovery_address(&wallet, owner),
+            initiator: *initiator,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CancelRecovery {}.data(),
+    };
+    test_wallet.process(&[ix], &[user]).await
+}
+
+async fn execute_recovery(
+    test_wallet: &mut TestWallet,
+    initiator: &Pubkey,
+    owner: &Pubkey,
+    new_owner: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ExecuteRecovery {
+            payer: payer.pubkey(),
+            wallet,
+            guardian_set: guardian_set_address(&wallet),
+            recovery: recovery_address(&wallet, owner),
+            initiator: *initiator,
+            wallet_auth: wallet_auth_address(&wallet, owner),
+            new_wallet_auth: wallet_auth_address(&wallet, new_owner),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ExecuteRecovery {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await
+}
+
+#[tokio::test]
+async fn proposal_goes_stale_when_an_owner_is_recovered() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let payer = test_wallet.payer();
+    let guardians = test_wallet.set_guardians(1, 1).await;
+    let owner = test_wallet.owners[1].pubkey();
+    let new_owner = Keypair::new().pubkey();
+    test_wallet
+        .initiate_recovery(&guardians[0], &owner, &new_owner)
+        .await
+        .unwrap();
+    test_wallet
+        .advance_clock(GuardianSet::MIN_RECOVERY_DELAY)
+        .await;
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::ChangeProposalLifetime {
+                duration: 2 * 24 * 60 * 60,
+            },
+        )
+        .await
+        .unwrap();
+    execute_recovery(&mut test_wallet, &guardians[0].pubkey(), &owner, &new_owner)
+        .await
+        .unwrap();
+
+    let ix = change_lifetime_ix(&test_wallet.wallet, &proposal, &payer.pubkey());
+    let error = test_wallet.process(&[ix], &[&payer]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::StaleProposal);
+}
+
+#[tokio::test]
+async fn recover_owner() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let guardians = test_wallet.set_guardians(3, 2).await;
+    let owner = test_wallet.owners[1].pubkey();
+    let new_owner = Keypair::new().pubkey();
+    let wallet_auth = test_wallet.wallet_auth(&owner).await.unwrap();
+
+    let outsider = Keypair::new();
+    test_wallet
+        .transfer_lamports(&outsider.pubkey(), 1_000_000_000)
+        .await;
+    let error = test_wallet
+        .initiate_recovery(&outsider, &owner, &new_owner)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::NotGuardian);
+
+    // the initiating guardian approves right away
+    test_wallet
+        .initiate_recovery(&guardians[0], &owner, &new_owner)
+        .await
+        .unwrap();
+    let error = approve_recovery(&mut test_wallet, &guardians[0], &owner)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::AlreadyApproved);
+    let error = execute_recovery(&mut test_wallet, &guardians[0].pubkey(), &owner, &new_owner)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::RecoveryDelayNotPassed);
+
+    test_wallet
+        .advance_clock(GuardianSet::MIN_RECOVERY_DELAY)
+        .await;
+    let error = execute_recovery(&mut test_wallet, &guardians[0].pubkey(), &owner, &new_owner)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::NotEnoughApprovals);
+
+    approve_recovery(&mut test_wallet, &guardians[1], &owner)
+        .await
+        .unwrap();
+    execute_recovery(&mut test_wallet, &guardians[0].pubkey(), &owner, &new_owner)
+        .await
+        .unwrap();
+    assert!(test_wallet.wallet_auth(&owner).await.is_none());
+    assert!(test_wallet
+        .account(&recovery_address(&wallet, &owner))
+        .await
+        .is_none());
+    let new_wallet_auth = test_wallet.wallet_auth(&new_owner).await.unwrap();
+    assert_eq!(
+        (new_wallet_auth.id, new_wallet_auth.weight),
+        (wallet_auth.id, wallet_auth.weight)
+    );
+}
+
+#[tokio::test]
+async fn veto_recovery() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let guardians = test_wallet.set_guardians(1, 1).await;
+    let owner = test_wallet.owners[1].insecure_clone();
+    let new_owner = Keypair::new().pubkey();
+    test_wallet
+        .initiate_recovery(&guardians[0], &owner.pubkey(), &new_owner)
+        .await
+        .unwrap();
+    let recovery = recovery_address(&wallet, &owner.pubkey());
+    let recovery_rent = test_wallet.account(&recovery).await.unwrap().lamports;
+    let guardian_balance = test_wallet
+        .account(&guardians[0].pubkey())
+        .await
+        .unwrap()
+        .lamports;
+
+    // the owner whose key is being recovered vetoes, the handler relies on the accounts
+    // constraint to close the recovery
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::VetoRecovery {
+            user: owner.pubkey(),
+            wallet,
+            wallet_auth: wallet_auth_address(&wallet, &owner.pubkey()),
+            recovery,
+            initiator: guardians[0].pubkey(),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::VetoRecovery {}.data(),
+    };
+    test_wallet.process(&[ix], &[&owner]).await.unwrap();
+    assert!(test_wallet.account(&recovery).await.is_none());
+    assert_eq!(
+        test_wallet
+            .account(&guardians[0].pubkey())
+            .await
+            .unwrap()
+            .lamports,
+        guardian_balance + recovery_rent
+    );
+
+    test_wallet
+        .advance_clock(GuardianSet::MIN_RECOVERY_DELAY)
+        .await;
+    assert!(execute_recovery(
+        &mut test_wallet,
+        &guardians[0].pubkey(),
+        &owner.pubkey(),
+        &new_owner
+    )
+    .await
+    .is_err());
+    assert!(test_wallet.wallet_auth(&owner.pubkey()).await.is_some());
+}
+
+#[tokio::test]
+async fn cancel_recovery_by_guardian() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let guardians = test_wallet.set_guardians(2, 1).await;
+    let owner = test_wallet.owners[1].pubkey();
+    test_wallet
+        .initiate_recovery(&guardians[0], &owner, &Keypair::new().pubkey())
+        .await
+        .unwrap();
+
+    let payer = test_wallet.payer();
+    let error = cancel_recovery(&mut test_wallet, &payer, &guardians[0].pubkey(), &owner)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::NotGuardian);
+    cancel_recovery(
+        &mut test_wallet,
+        &guardians[1],
+        &guardians[0].pubkey(),
+        &owner,
+    )
+    .await
+    .unwrap();
+    assert!(test_wallet
+        .account(&recovery_address(&wallet, &owner))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn close_stale_recovery() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let guardians = test_wallet.set_guardians(2, 2).await;
+    let owner = test_wallet.owners[1].pubkey();
+    test_wallet
+        .initiate_recovery(&guardians[0], &owner, &Keypair::new().pubkey())
+        .await
+        .unwrap();
+
+    // replacing the guardians leaves the recovery stale
+    test_wallet.advance_clock(1).await;
+    let new_guardians = test_wallet.set_guardians(1, 1).await;
+    let error = approve_recovery(&mut test_wallet, &new_guardians[0], &owner)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::StaleRecovery);
+
+    let payer = test_wallet.payer();
+    cancel_recovery(&mut test_wallet, &payer, &guardians[0].pubkey(), &owner)
+        .await
+        .unwrap();
+    assert!(test_wallet
+        .account(&recovery_address(&wallet, &owner))
+        .await
+        .is_none());
+}
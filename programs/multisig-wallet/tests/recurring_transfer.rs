@@ -0,0 +1,178 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_anchor_error, assert_wallet_error, spl_token, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::ProposalType;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+struct Schedule {
+    test_wallet: TestWallet,
+    mint: Pubkey,
+    // wallet account
+    send_account: Pubkey,
+    receive_account: Pubkey,
+    schedule: Pubkey,
+}
+
+fn schedule_address(wallet: &Pubkey, proposal: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"schedule", wallet.as_ref(), proposal.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+// the wallet pays 100 tokens every hour, twice
+async fn started_schedule() -> Schedule {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let authority = test_wallet.authority();
+    let mint = test_wallet.create_mint(0).await;
+    let send_account = test_wallet.create_token_account(&mint, &authority).await;
+    let receive_account = test_wallet
+        .create_token_account(&mint, &Pubkey::new_unique())
+        .await;
+    test_wallet.mint_to(&mint, &send_account, 1000).await;
+
+    let recurring_transfer = ProposalType::RecurringTransfer {
+        mint,
+        receive_account,
+        amount: 100,
+        interval: 60 * 60,
+        count: 2,
+    };
+    let proposal = test_wallet
+        .propose_with_token_accounts(0, recurring_transfer, &mint, &receive_account)
+        .await
+        .unwrap();
+    let schedule = schedule_address(&wallet, &proposal);
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::StartRecurringTransfer {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            payer: payer.pubkey(),
+            schedule,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::StartRecurringTransfer {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    Schedule {
+        test_wallet,
+        mint,
+        send_account,
+        receive_account,
+        schedule,
+    }
+}
+
+fn crank_ix(schedule: &Schedule) -> Instruction {
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CrankPayment {
+            wallet: schedule.test_wallet.wallet,
+            schedule: schedule.schedule,
+            schedule_payer: schedule.test_wallet.payer().pubkey(),
+            send_account: schedule.send_account,
+            receive_account: schedule.receive_account,
+            mint: schedule.mint,
+            wallet_authority: schedule.test_wallet.authority(),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CrankPayment {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn pays_due_instalments() {
+    let mut schedule = started_schedule().await;
+    let payer = schedule.test_wallet.payer();
+    let receive_account = schedule.receive_account;
+    let ix = crank_ix(&schedule);
+    schedule
+        .test_wallet
+        .process(&[ix], &[&payer])
+        .await
+        .unwrap();
+    assert_eq!(
+        schedule.test_wallet.token_balance(&receive_account).await,
+        100
+    );
+
+    let ix = crank_ix(&schedule);
+    let error = schedule
+        .test_wallet
+        .process(&[ix], &[&payer])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::PaymentNotDue);
+
+    // the last instalment closes the schedule
+    schedule.test_wallet.advance_clock(60 * 60).await;
+    let ix = crank_ix(&schedule);
+    schedule
+        .test_wallet
+        .process(&[ix], &[&payer])
+        .await
+        .unwrap();
+    assert_eq!(
+        schedule.test_wallet.token_balance(&receive_account).await,
+        200
+    );
+    let address = schedule.schedule;
+    assert!(schedule.test_wallet.account(&address).await.is_none());
+}
+
+#[tokio::test]
+async fn cancel_schedule() {
+    let mut schedule = started_schedule().await;
+    let wallet = schedule.test_wallet.wallet;
+    let payer = schedule.test_wallet.payer();
+    let proposal = schedule
+        .test_wallet
+        .propose(
+            0,
+            ProposalType::CancelRecurringTransfer {
+                schedule: schedule.schedule,
+            },
+        )
+        .await
+        .unwrap();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CancelRecurringTransfer {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            schedule: schedule.schedule,
+            schedule_payer: payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CancelRecurringTransfer {}.data(),
+    };
+    schedule
+        .test_wallet
+        .process(&[ix], &[&payer])
+        .await
+        .unwrap();
+    let address = schedule.schedule;
+    assert!(schedule.test_wallet.account(&address).await.is_none());
+
+    let ix = crank_ix(&schedule);
+    let error = schedule
+        .test_wallet
+        .process(&[ix], &[&payer])
+        .await
+        .unwrap_err();
+    assert_anchor_error(error, ErrorCode::AccountNotInitialized);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, stake, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_wallet_error, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::ProposalType;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    stake::state::StakeStateV2,
+    vote::{
+        instruction::{create_account_with_config, CreateVoteAccountConfig},
+        state::{VoteInit, VoteState},
+    },
+};
+
+const STAKE_LAMPORTS: u64 = 5_000_000_000;
+
+fn stake_address(wallet: &Pubkey, proposal: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"stake", wallet.as_ref(), proposal.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+fn create_stake_ix(test_wallet: &TestWallet, proposal: &Pubkey, proposer: &Pubkey) -> Instruction {
+    let wallet = test_wallet.wallet;
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CreateStake {
+            wallet,
+            proposal: *proposal,
+            vote_count: vote_count_address(&wallet, proposal),
+            proposer: *proposer,
+            wallet_authority: test_wallet.authority(),
+            stake: stake_address(&wallet, proposal),
+            rent: sysvar::rent::ID,
+            stake_program: stake::program::ID,
+            system_program: anchor_lang::solana_program::system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CreateStake {}.data(),
+    }
+}
+
+fn delegate_stake_ix(
+    test_wallet: &TestWallet,
+    proposal: &Pubkey,
+    proposer: &Pubkey,
+    stake: &Pubkey,
+    vote_account: Option<Pubkey>,
+) -> Instruction {
+    let wallet = test_wallet.wallet;
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ManageStake {
+            wallet,
+            proposal: *proposal,
+            vote_count: vote_count_address(&wallet, proposal),
+            proposer: *proposer,
+            wallet_authority: test_wallet.authority(),
+            stake: *stake,
+            clock: sysvar::clock::ID,
+            stake_history: sysvar::stake_history::ID,
+            stake_program: stake::program::ID,
+            vote_account,
+            stake_config: Some(stake::config::ID),
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::DelegateStake {}.data(),
+    }
+}
+
+// creates a stake account funded by the wallet authority, returning its address
+async fn create_stake(test_wallet: &mut TestWallet) -> Pubkey {
+    let authority = test_wallet.authority();
+    test_wallet
+        .transfer_lamports(&authority, 2 * STAKE_LAMPORTS)
+        .await;
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::CreateStake {
+                lamports: STAKE_LAMPORTS,
+            },
+        )
+        .await
+        .unwrap();
+    let stake = stake_address(&test_wallet.wallet, &proposal);
+    // lamports sent to the stake address ahead of time must not block creating it
+    test_wallet.transfer_lamports(&stake, 1_000_000).await;
+
+    let payer = test_wallet.payer();
+    let ix = create_stake_ix(test_wallet, &proposal, &payer.pubkey());
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    stake
+}
+
+async fn create_vote_account(test_wallet: &mut TestWallet) -> Pubkey {
+    let payer = test_wallet.payer();
+    let vote_account = Keypair::new();
+    let node = Keypair::new();
+    let rent = test_wallet.context.banks_client.get_rent().await.unwrap();
+    let ixs = create_account_with_config(
+        &payer.pubkey(),
+        &vote_account.pubkey(),
+        &VoteInit {
+            node_pubkey: node.pubkey(),
+            authorized_voter: node.pubkey(),
+            authorized_withdrawer: payer.pubkey(),
+            commission: 0,
+        },
+        rent.minimum_balance(VoteState::size_of()),
+        CreateVoteAccountConfig {
+            space: VoteState::size_of() as u64,
+            ..CreateVoteAccountConfig::default()
+        },
+    );
+    test_wallet
+        .process(&ixs, &[&payer, &vote_account, &node])
+        .await
+        .unwrap();
+    vote_account.pubkey()
+}
+
+#[tokio::test]
+async fn create_prefunded_stake() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let stake = create_stake(&mut test_wallet).await;
+
+    let account = test_wallet.account(&stake).await.unwrap();
+    assert_eq!(account.owner, stake::program::ID);
+    assert_eq!(account.lamports, STAKE_LAMPORTS + 1_000_000);
+    match account.deserialize_data::<StakeStateV2>().unwrap() {
+        StakeStateV2::Initialized(meta) => {
+            assert_eq!(meta.authorized.staker, test_wallet.authority());
+            assert_eq!(meta.authorized.withdrawer, test_wallet.authority());
+        }
+        state => panic!("unexpected stake state {:?}", state),
+    }
+}
+
+#[tokio::test]
+async fn delegate_stake() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let stake = create_stake(&mut test_wallet).await;
+    let vote_account = create_vote_account(&mut test_wallet).await;
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::DelegateStake {
+                stake,
+                vote_account,
+            },
+        )
+        .await
+        .unwrap();
+    let payer = test_wallet.payer();
+
+    // the vote account is optional in the accounts but required for delegating
+    let ix = delegate_stake_ix(&test_wallet, &proposal, &payer.pubkey(), &stake, None);
+    let error = test_wallet.process(&[ix], &[&payer]).await.unwrap_err();
+    assert_wallet_error(error, WalletError::InsufficientAccounts);
+
+    let ix = delegate_stake_ix(
+        &test_wallet,
+        &proposal,
+        &payer.pubkey(),
+        &stake,
+        Some(vote_account),
+    );
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    let account = test_wallet.account(&stake).await.unwrap();
+    match account.deserialize_data::<StakeStateV2>().unwrap() {
+        StakeStateV2::Stake(_, delegation, _) => {
+            assert_eq!(delegation.delegation.voter_pubkey, vote_account)
+        }
+        state => panic!("unexpected stake state {:?}", state),
+    }
+}
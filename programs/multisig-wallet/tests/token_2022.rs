@@ -0,0 +1,181 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::{Account, Mint},
+};
+use common::{assert_wallet_error, TestWallet};
+use multisig_wallet::error::WalletError;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+
+// creates a token-2022 mint, a fee in basis points adds the transfer fee extension
+async fn create_mint(test_wallet: &mut TestWallet, fee: Option<u16>) -> Pubkey {
+    let payer = test_wallet.payer();
+    let mint = Keypair::new();
+    let extensions: &[ExtensionType] = match fee {
+        Some(_) => &[ExtensionType::TransferFeeConfig],
+        None => &[],
+    };
+    let len = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+    let rent = test_wallet.context.banks_client.get_rent().await.unwrap();
+    let mut instructions = vec![system_instruction::create_account(
+        &payer.pubkey(),
+        &mint.pubkey(),
+        rent.minimum_balance(len),
+        len as u64,
+        &spl_token_2022::ID,
+    )];
+    if let Some(fee) = fee {
+        instructions.push(
+            transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                &mint.pubkey(),
+                None,
+                None,
+                fee,
+                u64::MAX,
+            )
+            .unwrap(),
+        );
+    }
+    instructions.push(
+        spl_token_2022::instruction::initialize_mint2(
+            &spl_token_2022::ID,
+            &mint.pubkey(),
+            &payer.pubkey(),
+            None,
+            0,
+        )
+        .unwrap(),
+    );
+    test_wallet
+        .process(&instructions, &[&payer, &mint])
+        .await
+        .unwrap();
+    mint.pubkey()
+}
+
+// the wallet creates its own associated token account through the program
+async fn create_wallet_account(test_wallet: &mut TestWallet, mint: &Pubkey) -> Pubkey {
+    let payer = test_wallet.payer();
+    let authority = test_wallet.authority();
+    let account =
+        get_associated_token_address_with_program_id(&authority, mint, &spl_token_2022::ID);
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CreateTokenAccount {
+            payer: payer.pubkey(),
+            wallet: test_wallet.wallet,
+            wallet_authority: authority,
+            mint: *mint,
+            account,
+            token_program: spl_token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CreateTokenAccount {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    account
+}
+
+async fn create_account(test_wallet: &mut TestWallet, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    let payer = test_wallet.payer();
+    let account = Keypair::new();
+    let mint_data = test_wallet.account(mint).await.unwrap().data;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data).unwrap();
+    let extensions = ExtensionType::get_required_init_account_extensions(
+        &mint_state.get_extension_types().unwrap(),
+    );
+    let len = ExtensionType::try_calculate_account_len::<Account>(&extensions).unwrap();
+    let rent = test_wallet.context.banks_client.get_rent().await.unwrap();
+    test_wallet
+        .process(
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &account.pubkey(),
+                    rent.minimum_balance(len),
+                    len as u64,
+                    &spl_token_2022::ID,
+                ),
+                spl_token_2022::instruction::initialize_account3(
+                    &spl_token_2022::ID,
+                    &account.pubkey(),
+                    mint,
+                    owner,
+                )
+                .unwrap(),
+            ],
+            &[&payer, &account],
+        )
+        .await
+        .unwrap();
+    account.pubkey()
+}
+
+async fn mint_to(test_wallet: &mut TestWallet, mint: &Pubkey, account: &Pubkey, amount: u64) {
+    let payer = test_wallet.payer();
+    let ix = spl_token_2022::instruction::mint_to(
+        &spl_token_2022::ID,
+        mint,
+        account,
+        &payer.pubkey(),
+        &[],
+        amount,
+    )
+    .unwrap();
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+}
+
+async fn balance(test_wallet: &mut TestWallet, account: &Pubkey) -> u64 {
+    let data = test_wallet.account(account).await.unwrap().data;
+    StateWithExtensions::<Account>::unpack(&data)
+        .unwrap()
+        .base
+        .amount
+}
+
+#[tokio::test]
+async fn transfer_token_2022() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let mint = create_mint(&mut test_wallet, None).await;
+    let send_account = create_wallet_account(&mut test_wallet, &mint).await;
+    let receive_account = create_account(&mut test_wallet, &mint, &Pubkey::new_unique()).await;
+    mint_to(&mut test_wallet, &mint, &send_account, 1000).await;
+
+    let proposal = test_wallet
+        .propose_transfer(0, &mint, &receive_account, 300, 0)
+        .await
+        .unwrap();
+    test_wallet
+        .execute_transfer(&proposal, &send_account, &receive_account, &mint)
+        .await
+        .unwrap();
+    assert_eq!(balance(&mut test_wallet, &send_account).await, 700);
+    assert_eq!(balance(&mut test_wallet, &receive_account).await, 300);
+    assert!(test_wallet.proposal(&proposal).await.is_none());
+}
+
+#[tokio::test]
+async fn transfer_fee_exceeding_the_amount() {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    // a fee of 100% withholds the whole amount
+    let mint = create_mint(&mut test_wallet, Some(10_000)).await;
+    let receive_account = create_account(&mut test_wallet, &mint, &Pubkey::new_unique()).await;
+
+    let error = test_wallet
+        .propose_transfer(0, &mint, &receive_account, 1, 0)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::TransferFeeExceedsAmount);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_wallet_error, transfer_tiers_address, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::{ProposalType, TransferTier};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+struct Tiers {
+    test_wallet: TestWallet,
+    mint: Pubkey,
+    // wallet account
+    send_account: Pubkey,
+    receive_account: Pubkey,
+}
+
+// a 1 of 2 wallet holding 1000 tokens, transfers of 500 or more need both owners
+async fn wallet_with_tiers() -> Tiers {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let authority = test_wallet.authority();
+    let mint = test_wallet.create_mint(0).await;
+    let send_account = test_wallet.create_token_account(&mint, &authority).await;
+    let receive_account = test_wallet
+        .create_token_account(&mint, &Pubkey::new_unique())
+        .await;
+    test_wallet.mint_to(&mint, &send_account, 1000).await;
+
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::SetTransferTiers {
+                mint,
+                tiers: vec![
+                    TransferTier {
+                        min_amount: 0,
+                        m: 1,
+                    },
+                    TransferTier {
+                        min_amount: 500,
+                        m: 2,
+                    },
+                ],
+            },
+        )
+        .await
+        .unwrap();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::SetTransferTiers {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            payer: payer.pubkey(),
+            transfer_tiers: transfer_tiers_address(&wallet),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::SetTransferTiers {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    Tiers {
+        test_wallet,
+        mint,
+        send_account,
+        receive_account,
+    }
+}
+
+#[tokio::test]
+async fn small_transfer_with_one_vote() {
+    let mut tiers = wallet_with_tiers().await;
+    let (mint, send_account, receive_account) =
+        (tiers.mint, tiers.send_account, tiers.receive_account);
+    let proposal = tiers
+        .test_wallet
+        .propose_transfer(0, &mint, &receive_account, 100, 0)
+        .await
+        .unwrap();
+    tiers
+        .test_wallet
+        .execute_transfer(&proposal, &send_account, &receive_account, &mint)
+        .await
+        .unwrap();
+    assert_eq!(tiers.test_wallet.token_balance(&receive_account).await, 100);
+}
+
+#[tokio::test]
+async fn large_transfer_needs_both_owners() {
+    let mut tiers = wallet_with_tiers().await;
+    let (mint, send_account, receive_account) =
+        (tiers.mint, tiers.send_account, tiers.receive_account);
+    let proposal = tiers
+        .test_wallet
+        .propose_transfer(0, &mint, &receive_account, 600, 0)
+        .await
+        .unwrap();
+    let error = tiers
+        .test_wallet
+        .execute_transfer(&proposal, &send_account, &receive_account, &mint)
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::NotEnoughVotes);
+
+    tiers.test_wallet.vote(1, &proposal).await.unwrap();
+    tiers
+        .test_wallet
+        .execute_transfer(&proposal, &send_account, &receive_account, &mint)
+        .await
+        .unwrap();
+    assert_eq!(tiers.test_wallet.token_balance(&receive_account).await, 600);
+}
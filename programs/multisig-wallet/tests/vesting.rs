@@ -0,0 +1,242 @@
+mod common;
+
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::{assert_wallet_error, spl_token, vote_count_address, TestWallet};
+use multisig_wallet::error::WalletError;
+use multisig_wallet::state::ProposalType;
+use solana_sdk::{
+    clock::Clock,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+struct Vesting {
+    test_wallet: TestWallet,
+    beneficiary: Keypair,
+    mint: Pubkey,
+    // wallet account
+    send_account: Pubkey,
+    // beneficiary account
+    receive_account: Pubkey,
+    vesting: Pubkey,
+    escrow: Pubkey,
+}
+
+fn vesting_address(wallet: &Pubkey, proposal: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vesting", wallet.as_ref(), proposal.as_ref()],
+        &multisig_wallet::ID,
+    )
+    .0
+}
+
+fn escrow_address(vesting: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"escrow", vesting.as_ref()], &multisig_wallet::ID).0
+}
+
+// the wallet vests 1000 tokens over 1000 seconds with a cliff after 100 seconds
+async fn created_vesting() -> Vesting {
+    let mut test_wallet = TestWallet::new(common::program_test(), 1, 2).await;
+    let beneficiary = Keypair::new();
+    test_wallet
+        .transfer_lamports(&beneficiary.pubkey(), 1_000_000_000)
+        .await;
+    let authority = test_wallet.authority();
+    let mint = test_wallet.create_mint(0).await;
+    let send_account = test_wallet.create_token_account(&mint, &authority).await;
+    let receive_account = test_wallet
+        .create_token_account(&mint, &beneficiary.pubkey())
+        .await;
+    test_wallet.mint_to(&mint, &send_account, 1000).await;
+
+    let clock: Clock = test_wallet.context.banks_client.get_sysvar().await.unwrap();
+    let start = clock.unix_timestamp;
+    let proposal = test_wallet
+        .propose(
+            0,
+            ProposalType::CreateVesting {
+                mint,
+                beneficiary: beneficiary.pubkey(),
+                amount: 1000,
+                start,
+                cliff: start + 100,
+                end: start + 1000,
+            },
+        )
+        .await
+        .unwrap();
+    let wallet = test_wallet.wallet;
+    let payer = test_wallet.payer();
+    let vesting = vesting_address(&wallet, &proposal);
+    let escrow = escrow_address(&vesting);
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::CreateVesting {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            payer: payer.pubkey(),
+            vesting,
+            escrow,
+            send_account,
+            mint,
+            wallet_authority: authority,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::CreateVesting {}.data(),
+    };
+    test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    Vesting {
+        test_wallet,
+        beneficiary,
+        mint,
+        send_account,
+        receive_account,
+        vesting,
+        escrow,
+    }
+}
+
+fn claim_ix(vesting: &Vesting) -> Instruction {
+    Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ClaimVested {
+            beneficiary: vesting.beneficiary.pubkey(),
+            wallet: vesting.test_wallet.wallet,
+            vesting: vesting.vesting,
+            payer: vesting.test_wallet.payer().pubkey(),
+            escrow: vesting.escrow,
+            receive_account: vesting.receive_account,
+            mint: vesting.mint,
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ClaimVested {}.data(),
+    }
+}
+
+#[tokio::test]
+async fn claim_while_paused() {
+    let mut vesting = created_vesting().await;
+    assert_eq!(
+        vesting
+            .test_wallet
+            .token_balance(&vesting.send_account)
+            .await,
+        0
+    );
+    vesting.test_wallet.advance_clock(1000).await;
+    vesting.test_wallet.pause(1, &[]).await.unwrap();
+
+    let ix = claim_ix(&vesting);
+    let beneficiary = vesting.beneficiary.insecure_clone();
+    let error = vesting
+        .test_wallet
+        .process(&[ix], &[&beneficiary])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::WalletPaused);
+    assert_eq!(
+        vesting.test_wallet.token_balance(&vesting.escrow).await,
+        1000
+    );
+}
+
+#[tokio::test]
+async fn claim_after_the_cliff() {
+    let mut vesting = created_vesting().await;
+    let beneficiary = vesting.beneficiary.insecure_clone();
+    vesting.test_wallet.advance_clock(500).await;
+    let ix = claim_ix(&vesting);
+    vesting
+        .test_wallet
+        .process(&[ix], &[&beneficiary])
+        .await
+        .unwrap();
+    assert_eq!(
+        vesting
+            .test_wallet
+            .token_balance(&vesting.receive_account)
+            .await,
+        500
+    );
+}
+
+#[tokio::test]
+async fn claim_before_the_cliff() {
+    let mut vesting = created_vesting().await;
+    let beneficiary = vesting.beneficiary.insecure_clone();
+    vesting.test_wallet.advance_clock(50).await;
+    let ix = claim_ix(&vesting);
+    let error = vesting
+        .test_wallet
+        .process(&[ix], &[&beneficiary])
+        .await
+        .unwrap_err();
+    assert_wallet_error(error, WalletError::NothingToClaim);
+}
+
+#[tokio::test]
+async fn clawback_unvested_tokens() {
+    let mut vesting = created_vesting().await;
+    let wallet = vesting.test_wallet.wallet;
+    let payer = vesting.test_wallet.payer();
+    vesting.test_wallet.advance_clock(400).await;
+    let proposal = vesting
+        .test_wallet
+        .propose(
+            0,
+            ProposalType::ClawbackVesting {
+                vesting: vesting.vesting,
+            },
+        )
+        .await
+        .unwrap();
+    let ix = Instruction {
+        program_id: multisig_wallet::ID,
+        accounts: multisig_wallet::accounts::ClawbackVesting {
+            wallet,
+            proposal,
+            vote_count: vote_count_address(&wallet, &proposal),
+            proposer: payer.pubkey(),
+            vesting: vesting.vesting,
+            payer: payer.pubkey(),
+            escrow: vesting.escrow,
+            receive_account: vesting.send_account,
+            mint: vesting.mint,
+            wallet_authority: vesting.test_wallet.authority(),
+            token_program: spl_token::ID,
+        }
+        .to_account_metas(None),
+        data: multisig_wallet::instruction::ClawbackVesting {}.data(),
+    };
+    vesting.test_wallet.process(&[ix], &[&payer]).await.unwrap();
+    assert_eq!(
+        vesting
+            .test_wallet
+            .token_balance(&vesting.send_account)
+            .await,
+        600
+    );
+
+    // what vested before the clawback can still be claimed
+    let beneficiary = vesting.beneficiary.insecure_clone();
+    vesting.test_wallet.advance_clock(600).await;
+    let ix = claim_ix(&vesting);
+    vesting
+        .test_wallet
+        .process(&[ix], &[&beneficiary])
+        .await
+        .unwrap();
+    assert_eq!(
+        vesting
+            .test_wallet
+            .token_balance(&vesting.receive_account)
+            .await,
+        400
+    );
+}